@@ -3,7 +3,7 @@ use shakmaty::{
     fen::Fen,
     perft,
     san::San,
-    zobrist::{Zobrist64, ZobristHash},
+    zobrist::{Zobrist, Zobrist64, ZobristHash},
     CastlingMode, Chess, EnPassantMode, Move, Position, Role, Square,
 };
 
@@ -59,6 +59,27 @@ fn bench_play_unchecked() -> Chess {
     pos
 }
 
+fn bench_zobrist_play_unchecked() -> Zobrist<Chess, Zobrist64> {
+    let fen = "rn1qkb1r/pbp2ppp/1p2p3/3n4/8/2N2NP1/PP1PPPBP/R1BQ1RK1 b kq -";
+    let pos: Zobrist<Chess, Zobrist64> = fen
+        .parse::<Fen>()
+        .expect("valid fen")
+        .into_position(CastlingMode::Chess960)
+        .expect("legal position");
+
+    let m = Move::Normal {
+        role: Role::Bishop,
+        from: Square::F8,
+        capture: None,
+        to: Square::E7,
+        promotion: None,
+    };
+
+    let mut pos = black_box(pos);
+    pos.play_unchecked(&m);
+    pos
+}
+
 fn bench_san_candidates() {
     let fen = "r2q1rk1/pb1nbppp/5n2/1p2p3/3NP3/P1NB4/1P2QPPP/R1BR2K1 w - -";
     let pos: Chess = fen
@@ -116,6 +137,7 @@ iai::main!(
     bench_kiwipete,
     bench_generate_moves,
     bench_play_unchecked,
+    bench_zobrist_play_unchecked,
     bench_san_candidates,
     bench_play_sans,
     bench_zobrist_hash,
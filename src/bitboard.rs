@@ -22,6 +22,22 @@ use crate::square::{File, Rank, Square};
 /// // . 1 . . 1 . . .
 /// // . 1 . . . 1 . .
 /// ```
+///
+/// # Layout
+///
+/// Bit `n` corresponds to [`Square`] `n` (little-endian rank-file mapping):
+/// the least significant bit is a1, followed by b1, ..., h1, a2, ..., and
+/// the most significant bit is h8. Use the tuple field or the [`From`]
+/// implementations to convert from and to `u64`. Engines with a different
+/// layout can usually be interoperated with using one of the
+/// transformations, like [`Bitboard::flip_vertical()`].
+///
+/// ```
+/// use shakmaty::{Bitboard, Square};
+///
+/// assert_eq!(Bitboard::from(Square::A1), Bitboard(1));
+/// assert_eq!(u64::from(Bitboard::from(Square::H8)), 1 << 63);
+/// ```
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
 pub struct Bitboard(pub u64);
 
@@ -234,6 +250,27 @@ impl Bitboard {
         Bitboard(self.0 & other.0).is_empty()
     }
 
+    /// Tests if `self` and `other` have at least one square in common. The
+    /// opposite of [`Bitboard::is_disjoint()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, Square};
+    ///
+    /// assert!(Bitboard::CORNERS.intersects(Square::H8));
+    /// assert!(!Bitboard::CORNERS.intersects(Bitboard::CENTER));
+    /// ```
+    #[inline]
+    pub fn intersects<T: Into<Bitboard>>(self, other: T) -> bool {
+        self.intersects_const(other.into())
+    }
+
+    /// Same as the `intersects` method, but usable in `const` contexts.
+    pub const fn intersects_const(self, other: Bitboard) -> bool {
+        !self.is_disjoint_const(other)
+    }
+
     /// Tests if `self` is a subset of `other`.
     ///
     /// # Examples
@@ -262,6 +299,7 @@ impl Bitboard {
     ///
     /// assert!(Bitboard::FULL.is_superset(Bitboard::LIGHT_SQUARES));
     /// ```
+    #[doc(alias = "contains_all")]
     #[inline]
     pub fn is_superset<T: Into<Bitboard>>(self, other: T) -> bool {
         self.is_superset_const(other.into())
@@ -411,6 +449,81 @@ impl Bitboard {
         }
     }
 
+    /// Extends each square towards the eighth rank.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, Square};
+    ///
+    /// let fill = Bitboard::from(Square::C3).north_fill();
+    /// assert_eq!(fill, Bitboard(0x0404_0404_0404_0000));
+    /// // . . 1 . . . . .
+    /// // . . 1 . . . . .
+    /// // . . 1 . . . . .
+    /// // . . 1 . . . . .
+    /// // . . 1 . . . . .
+    /// // . . 1 . . . . .
+    /// // . . . . . . . .
+    /// // . . . . . . . .
+    /// ```
+    #[must_use]
+    #[inline]
+    pub const fn north_fill(self) -> Bitboard {
+        let mut bb = self.0;
+        bb |= bb << 8;
+        bb |= bb << 16;
+        bb |= bb << 32;
+        Bitboard(bb)
+    }
+
+    /// Extends each square towards the first rank.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, Square};
+    ///
+    /// let fill = Bitboard::from(Square::C3).south_fill();
+    /// assert_eq!(fill, Bitboard(0x0004_0404));
+    /// // . . . . . . . .
+    /// // . . . . . . . .
+    /// // . . . . . . . .
+    /// // . . . . . . . .
+    /// // . . . . . . . .
+    /// // . . 1 . . . . .
+    /// // . . 1 . . . . .
+    /// // . . 1 . . . . .
+    /// ```
+    #[must_use]
+    #[inline]
+    pub const fn south_fill(self) -> Bitboard {
+        let mut bb = self.0;
+        bb |= bb >> 8;
+        bb |= bb >> 16;
+        bb |= bb >> 32;
+        Bitboard(bb)
+    }
+
+    /// Extends each square to its entire file.
+    ///
+    /// This is the set of files that contain at least one square, for
+    /// example to find files with pawns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, File, Square};
+    ///
+    /// let pawns = Bitboard::from(Square::B2) | Bitboard::from(Square::B4) | Bitboard::from(Square::G6);
+    /// assert_eq!(pawns.file_fill(), Bitboard::from(File::B) | Bitboard::from(File::G));
+    /// ```
+    #[must_use]
+    #[inline]
+    pub const fn file_fill(self) -> Bitboard {
+        Bitboard(self.north_fill().0 | self.south_fill().0)
+    }
+
     /// Mirror the bitboard vertically.
     ///
     /// # Examples
@@ -466,6 +579,35 @@ impl Bitboard {
         Bitboard(x)
     }
 
+    /// Mirror the bitboard horizontally if `mirror_if_queenside` is set and
+    /// `king` is on the queenside (files a to d).
+    ///
+    /// This maps features relative to the king into a frame where the king
+    /// is always on the kingside, as commonly used for king buckets in
+    /// neural network evaluation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, Square};
+    ///
+    /// let bitboard = Bitboard::from(Square::B2);
+    /// assert_eq!(
+    ///     bitboard.relative_to_king(Square::C1, true),
+    ///     Bitboard::from(Square::G2)
+    /// );
+    /// assert_eq!(bitboard.relative_to_king(Square::C1, false), bitboard);
+    /// assert_eq!(bitboard.relative_to_king(Square::G1, true), bitboard);
+    /// ```
+    #[must_use]
+    pub fn relative_to_king(self, king: Square, mirror_if_queenside: bool) -> Bitboard {
+        if mirror_if_queenside && king.file() <= File::D {
+            self.flip_horizontal()
+        } else {
+            self
+        }
+    }
+
     /// Mirror the bitboard at the a1-h8 diagonal.
     ///
     /// # Examples
@@ -849,6 +991,32 @@ impl fmt::Debug for Bitboard {
     }
 }
 
+/// Renders the bitboard as an 8x8 grid, with a1 in the bottom-left corner.
+/// Same as the [`Debug`](fmt::Debug) representation.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::Bitboard;
+///
+/// assert_eq!(
+///     Bitboard::CORNERS.to_string(),
+///     "1 . . . . . . 1\n\
+///      . . . . . . . .\n\
+///      . . . . . . . .\n\
+///      . . . . . . . .\n\
+///      . . . . . . . .\n\
+///      . . . . . . . .\n\
+///      . . . . . . . .\n\
+///      1 . . . . . . 1\n"
+/// );
+/// ```
+impl fmt::Display for Bitboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
 impl fmt::UpperHex for Bitboard {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fmt::UpperHex::fmt(&self.0, f)
@@ -1112,6 +1280,37 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_layout() {
+        for sq in Square::ALL {
+            assert_eq!(u64::from(Bitboard::from(sq)), 1 << u32::from(sq));
+            assert_eq!(Bitboard(1 << u32::from(sq)).single_square(), Some(sq));
+        }
+        assert_eq!(u64::from(Bitboard::from(Square::A1)), 1);
+        assert_eq!(u64::from(Bitboard::from(Square::H1)), 1 << 7);
+        assert_eq!(u64::from(Bitboard::from(Square::A8)), 1 << 56);
+        assert_eq!(u64::from(Bitboard::from(Square::H8)), 1 << 63);
+    }
+
+    #[test]
+    fn test_fills() {
+        for sq in Square::ALL {
+            let bb = Bitboard::from_square(sq);
+            assert_eq!(bb.file_fill(), Bitboard::from_file(sq.file()));
+            assert_eq!(bb.north_fill() & bb.south_fill(), bb);
+            assert_eq!(bb.north_fill() | bb.south_fill(), bb.file_fill());
+            assert_eq!(bb.north_fill().count(), 8 - usize::from(sq.rank()));
+        }
+
+        let pawns = Bitboard::from_square(Square::D3).with(Square::D6);
+        assert_eq!(
+            pawns.north_fill(),
+            Bitboard::from_file(File::D).without_const(Bitboard(0x0808))
+        );
+        assert_eq!(Bitboard::EMPTY.file_fill(), Bitboard::EMPTY);
+        assert_eq!(Bitboard::from_rank(Rank::Fifth).file_fill(), Bitboard::FULL);
+    }
+
     #[test]
     fn test_more_than_one() {
         assert!(!Bitboard(0).more_than_one());
@@ -1162,6 +1361,40 @@ mod tests {
         assert!(!Bitboard(1).is_empty());
     }
 
+    #[test]
+    fn test_set_relations() {
+        // Overlapping.
+        assert!(Bitboard::NORTH.intersects(Bitboard::EAST));
+        assert!(!Bitboard::NORTH.is_disjoint(Bitboard::EAST));
+        assert!(!Bitboard::NORTH.is_superset(Bitboard::EAST));
+
+        // Disjoint.
+        assert!(Bitboard::DARK_SQUARES.is_disjoint(Bitboard::LIGHT_SQUARES));
+        assert!(!Bitboard::DARK_SQUARES.intersects(Bitboard::LIGHT_SQUARES));
+        assert!(Bitboard::EMPTY.is_disjoint(Bitboard::EMPTY));
+
+        // Subset.
+        assert!(Bitboard::BACKRANKS.is_superset(Bitboard::CORNERS));
+        assert!(Bitboard::CORNERS.is_subset(Bitboard::BACKRANKS));
+        assert!(Bitboard::BACKRANKS.intersects(Bitboard::CORNERS));
+        assert!(Bitboard::CORNERS.is_superset(Bitboard::EMPTY));
+        assert!(!Bitboard::CORNERS.intersects(Bitboard::EMPTY));
+    }
+
+    #[test]
+    fn test_relative_to_king() {
+        let bitboard = Bitboard(0x1e22_2212_0e0a_1222);
+        for king in Square::ALL {
+            let expected = if king.file() <= File::D {
+                bitboard.flip_horizontal()
+            } else {
+                bitboard
+            };
+            assert_eq!(bitboard.relative_to_king(king, true), expected);
+            assert_eq!(bitboard.relative_to_king(king, false), bitboard);
+        }
+    }
+
     #[test]
     fn test_rank() {
         assert_eq!(Bitboard::from_rank(Rank::Fourth), Bitboard(0xff00_0000));
@@ -1176,6 +1409,23 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_display() {
+        use alloc::string::ToString as _;
+
+        for sq in Square::ALL {
+            let display = Bitboard::from(sq).to_string();
+            assert_eq!(display.matches('1').count(), 1);
+
+            let line = display
+                .lines()
+                .nth(7 - usize::from(sq.rank()))
+                .expect("8 lines");
+            assert_eq!(line.find('1'), Some(2 * usize::from(sq.file())), "{sq}");
+        }
+    }
+
     #[cfg(feature = "alloc")]
     #[test]
     fn test_upper_hex() {
@@ -23,6 +23,14 @@ use crate::{attacks, Bitboard, ByColor, ByRole, Color, File, Piece, Rank, Role,
 ///
 /// assert_eq!(board.piece_at(Square::E8), Some(Black.king()));
 /// ```
+///
+/// # Equality
+///
+/// [`Hash`], [`PartialEq`], and [`Eq`] consider only the piece placement. Two positions
+/// with equal boards are not necessarily the same position for the purpose
+/// of repetitions, which also depends on the side to move, castling rights,
+/// and en passant rights. Compare positions (or their
+/// [Zobrist hashes](crate::zobrist)) for that.
 #[derive(Clone, Eq, PartialEq, Hash)]
 pub struct Board {
     by_role: ByRole<Bitboard>,
@@ -223,6 +231,24 @@ impl Board {
             .single_square()
     }
 
+    /// The total number of pieces on the board.
+    #[inline]
+    pub const fn count(&self) -> usize {
+        self.occupied.count()
+    }
+
+    /// Tests if there is a piece on `sq`.
+    #[inline]
+    pub const fn is_occupied(&self, sq: Square) -> bool {
+        self.occupied.contains(sq)
+    }
+
+    /// Tests if there is no piece on `sq`.
+    #[inline]
+    pub const fn is_empty(&self, sq: Square) -> bool {
+        !self.is_occupied(sq)
+    }
+
     #[inline]
     pub fn color_at(&self, sq: Square) -> Option<Color> {
         self.by_color.find(|c| c.contains(sq))
@@ -551,6 +577,91 @@ mod tests {
         compare_trans(&Board::rotate_270, "8/8/7N/1np3B1/b2K1R2/r3Q3/qkPNBRQK/8");
     }
 
+    #[test]
+    fn test_bitboard_accessors() {
+        let board: Board = "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR"
+            .parse()
+            .expect("valid fen");
+
+        assert_eq!(
+            board.by_color(White) | board.by_color(Black),
+            board.occupied()
+        );
+        assert!(board.by_color(White).is_disjoint(board.by_color(Black)));
+
+        let mut union = Bitboard::EMPTY;
+        for role in Role::ALL {
+            assert!(union.is_disjoint(board.by_role(role)));
+            union |= board.by_role(role);
+
+            for color in Color::ALL {
+                assert_eq!(
+                    board.by_piece(role.of(color)),
+                    board.by_role(role) & board.by_color(color)
+                );
+            }
+        }
+        assert_eq!(union, board.occupied());
+
+        assert_eq!(board.by_piece(White.queen()), Bitboard::from(Square::H5));
+        assert_eq!(board.by_role(Role::Knight).count(), 4);
+    }
+
+    #[test]
+    fn test_count_and_occupancy() {
+        let board = Board::default();
+        assert_eq!(board.count(), 32);
+        assert!(board.is_occupied(Square::E1));
+        assert!(!board.is_empty(Square::E1));
+        assert!(board.is_empty(Square::E4));
+        assert!(!board.is_occupied(Square::E4));
+
+        assert_eq!(Board::empty().count(), 0);
+        assert!(Square::ALL
+            .into_iter()
+            .all(|sq| Board::empty().is_empty(sq)));
+    }
+
+    #[test]
+    fn test_swap_colors() {
+        let board: Board = "4k3/8/8/8/8/8/PPP5/RNBQK3".parse().expect("valid fen");
+        let swapped = board.clone().into_swapped_colors();
+
+        assert_eq!(swapped.white(), board.black());
+        assert_eq!(swapped.black(), board.white());
+        assert_eq!(swapped.occupied(), board.occupied());
+        assert_eq!(swapped.material_side(Black), board.material_side(White));
+        for sq in board.occupied() {
+            assert_eq!(swapped.piece_at(sq), board.piece_at(sq).map(Piece::flip));
+        }
+        assert_eq!(swapped.into_swapped_colors(), board);
+    }
+
+    #[test]
+    fn test_eq_ignores_position_state() {
+        use crate::{fen::Fen, CastlingMode, Chess, Position};
+
+        let setup = |fen: &str| -> Chess {
+            fen.parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position")
+        };
+
+        let a = setup("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1");
+        let b = setup("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq - 7 20");
+        let c = setup("r3k2r/8/8/3pP3/8/8/8/R3K2R w Kk - 0 1");
+        let d = setup("r3k2r/8/8/3pP3/8/8/8/R3K2R b KQkq - 0 1");
+
+        for other in [&b, &c, &d] {
+            assert_eq!(a.board(), other.board());
+            assert_ne!(a, *other);
+        }
+
+        let e = setup("r3k2r/8/8/3pP3/8/8/8/R4RK1 w kq - 0 1");
+        assert_ne!(a.board(), e.board());
+    }
+
     #[test]
     fn test_from_bitboards() {
         let (by_role, by_color) = Board::default().into_bitboards();
@@ -0,0 +1,178 @@
+use core::{cell::OnceCell, num::NonZeroU32};
+
+use crate::{
+    Bitboard, Board, ByColor, ByRole, Castles, CastlingMode, CastlingSide, Color, EnPassantMode,
+    FromSetup, Move, MoveList, Outcome, Position, PositionError, RemainingChecks, Role, Setup,
+    Square,
+};
+
+/// A [`Position`] wrapper that memoizes the legal moves of the wrapped
+/// position.
+///
+/// The legal moves are generated on the first call to
+/// [`Position::legal_moves()`] and returned from the cache on subsequent
+/// calls, until the position is changed with [`Position::play_unchecked()`].
+/// This is useful for user interfaces that query the legal moves many times
+/// per position.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{CachedPosition, Chess, Position};
+///
+/// let pos: CachedPosition<Chess> = CachedPosition::default();
+/// assert_eq!(pos.legal_moves().len(), 20);
+/// assert_eq!(pos.legal_moves().len(), 20); // From cache
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CachedPosition<P> {
+    pos: P,
+    legal_moves: OnceCell<MoveList>,
+}
+
+impl<P> CachedPosition<P> {
+    /// Wraps a position, with an empty cache.
+    pub const fn new(pos: P) -> CachedPosition<P> {
+        CachedPosition {
+            pos,
+            legal_moves: OnceCell::new(),
+        }
+    }
+
+    /// Unwraps the position, discarding the cache.
+    pub fn into_inner(self) -> P {
+        self.pos
+    }
+}
+
+impl<P: Position + FromSetup> FromSetup for CachedPosition<P> {
+    fn from_setup(setup: Setup, mode: CastlingMode) -> Result<Self, PositionError<Self>> {
+        P::from_setup(setup, mode).map(CachedPosition::new).map_err(
+            |PositionError { pos, errors }| PositionError {
+                pos: CachedPosition::new(pos),
+                errors,
+            },
+        )
+    }
+}
+
+impl<P: Position> Position for CachedPosition<P> {
+    fn board(&self) -> &Board {
+        self.pos.board()
+    }
+
+    fn promoted(&self) -> Bitboard {
+        self.pos.promoted()
+    }
+
+    fn pockets(&self) -> Option<&ByColor<ByRole<u8>>> {
+        self.pos.pockets()
+    }
+
+    fn turn(&self) -> Color {
+        self.pos.turn()
+    }
+
+    fn castles(&self) -> &Castles {
+        self.pos.castles()
+    }
+
+    fn maybe_ep_square(&self) -> Option<Square> {
+        self.pos.maybe_ep_square()
+    }
+
+    fn remaining_checks(&self) -> Option<&ByColor<RemainingChecks>> {
+        self.pos.remaining_checks()
+    }
+
+    fn halfmoves(&self) -> u32 {
+        self.pos.halfmoves()
+    }
+
+    fn fullmoves(&self) -> NonZeroU32 {
+        self.pos.fullmoves()
+    }
+
+    fn into_setup(self, mode: EnPassantMode) -> Setup {
+        self.pos.into_setup(mode)
+    }
+
+    fn legal_moves(&self) -> MoveList {
+        self.legal_moves
+            .get_or_init(|| self.pos.legal_moves())
+            .clone()
+    }
+
+    fn san_candidates(&self, role: Role, to: Square) -> MoveList {
+        self.pos.san_candidates(role, to)
+    }
+
+    fn castling_moves(&self, side: CastlingSide) -> MoveList {
+        self.pos.castling_moves(side)
+    }
+
+    fn en_passant_moves(&self) -> MoveList {
+        self.pos.en_passant_moves()
+    }
+
+    fn capture_moves(&self) -> MoveList {
+        self.pos.capture_moves()
+    }
+
+    fn promotion_moves(&self) -> MoveList {
+        self.pos.promotion_moves()
+    }
+
+    fn is_irreversible(&self, m: &Move) -> bool {
+        self.pos.is_irreversible(m)
+    }
+
+    fn king_attackers(&self, square: Square, attacker: Color, occupied: Bitboard) -> Bitboard {
+        self.pos.king_attackers(square, attacker, occupied)
+    }
+
+    fn is_variant_end(&self) -> bool {
+        self.pos.is_variant_end()
+    }
+
+    fn has_insufficient_material(&self, color: Color) -> bool {
+        self.pos.has_insufficient_material(color)
+    }
+
+    fn variant_outcome(&self) -> Option<Outcome> {
+        self.pos.variant_outcome()
+    }
+
+    fn play_unchecked(&mut self, m: &Move) {
+        self.legal_moves.take();
+        self.pos.play_unchecked(m);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{uci::UciMove, Chess};
+
+    #[test]
+    fn test_cached_legal_moves() {
+        let mut pos: CachedPosition<Chess> = CachedPosition::default();
+        assert!(pos.legal_moves.get().is_none());
+
+        let moves = pos.legal_moves();
+        assert_eq!(pos.legal_moves.get(), Some(&moves));
+        assert_eq!(pos.legal_moves(), moves);
+
+        let m = "e2e4"
+            .parse::<UciMove>()
+            .expect("valid uci")
+            .to_move(&pos)
+            .expect("legal uci");
+        pos.play_unchecked(&m);
+        assert!(pos.legal_moves.get().is_none());
+
+        let moves = pos.legal_moves();
+        assert_eq!(moves, pos.clone().into_inner().legal_moves());
+        assert_eq!(pos.legal_moves.get(), Some(&moves));
+    }
+}
@@ -41,6 +41,40 @@ impl CastlingSide {
         }
     }
 
+    /// Gets the lowercase letter for the castling side, as used for black
+    /// castling rights in FEN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::CastlingSide;
+    ///
+    /// assert_eq!(CastlingSide::QueenSide.char(), 'q');
+    /// ```
+    pub const fn char(self) -> char {
+        match self {
+            CastlingSide::KingSide => 'k',
+            CastlingSide::QueenSide => 'q',
+        }
+    }
+
+    /// Gets the uppercase letter for the castling side, as used for white
+    /// castling rights in FEN.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::CastlingSide;
+    ///
+    /// assert_eq!(CastlingSide::KingSide.upper_char(), 'K');
+    /// ```
+    pub const fn upper_char(self) -> char {
+        match self {
+            CastlingSide::KingSide => 'K',
+            CastlingSide::QueenSide => 'Q',
+        }
+    }
+
     pub const fn king_to_file(self) -> File {
         match self {
             CastlingSide::KingSide => File::G,
@@ -254,4 +288,17 @@ mod tests {
         assert_eq!(CastlingSide::KingSide as usize, 0);
         assert_eq!(CastlingSide::QueenSide as usize, 1);
     }
+
+    #[test]
+    fn test_castling_side_targets() {
+        assert_eq!(CastlingSide::KingSide.king_to_file(), File::G);
+        assert_eq!(CastlingSide::KingSide.rook_to_file(), File::F);
+        assert_eq!(CastlingSide::QueenSide.king_to_file(), File::C);
+        assert_eq!(CastlingSide::QueenSide.rook_to_file(), File::D);
+
+        assert_eq!(CastlingSide::KingSide.king_to(Color::White), Square::G1);
+        assert_eq!(CastlingSide::KingSide.rook_to(Color::Black), Square::F8);
+        assert_eq!(CastlingSide::QueenSide.king_to(Color::Black), Square::C8);
+        assert_eq!(CastlingSide::QueenSide.rook_to(Color::White), Square::D1);
+    }
 }
@@ -63,6 +63,16 @@ impl Color {
         }
     }
 
+    /// Selects `white` or `black`, depending on the color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::Color;
+    ///
+    /// assert_eq!(Color::White.fold_wb("white", "black"), "white");
+    /// assert_eq!(Color::Black.fold_wb(1, -1), -1);
+    /// ```
     #[inline]
     pub fn fold_wb<T>(self, white: T, black: T) -> T {
         match self {
@@ -105,31 +115,57 @@ impl Color {
         }
     }
 
+    /// Gets the pawn of this color. Same as [`Role::of()`] with
+    /// `Role::Pawn`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Color, Piece, Role};
+    ///
+    /// assert_eq!(
+    ///     Color::White.pawn(),
+    ///     Piece {
+    ///         color: Color::White,
+    ///         role: Role::Pawn
+    ///     }
+    /// );
+    /// ```
     #[inline]
     pub const fn pawn(self) -> Piece {
         Role::Pawn.of(self)
     }
 
+    /// Gets the knight of this color. Same as [`Role::of()`] with
+    /// `Role::Knight`.
     #[inline]
     pub const fn knight(self) -> Piece {
         Role::Knight.of(self)
     }
 
+    /// Gets the bishop of this color. Same as [`Role::of()`] with
+    /// `Role::Bishop`.
     #[inline]
     pub const fn bishop(self) -> Piece {
         Role::Bishop.of(self)
     }
 
+    /// Gets the rook of this color. Same as [`Role::of()`] with
+    /// `Role::Rook`.
     #[inline]
     pub const fn rook(self) -> Piece {
         Role::Rook.of(self)
     }
 
+    /// Gets the queen of this color. Same as [`Role::of()`] with
+    /// `Role::Queen`.
     #[inline]
     pub const fn queen(self) -> Piece {
         Role::Queen.of(self)
     }
 
+    /// Gets the king of this color. Same as [`Role::of()`] with
+    /// `Role::King`.
     #[inline]
     pub const fn king(self) -> Piece {
         Role::King.of(self)
@@ -396,3 +432,24 @@ impl<T> IntoIterator for ByColor<T> {
         [self.white, self.black].into_iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_piece_constructors() {
+        for color in Color::ALL {
+            for (role, piece) in [
+                (Role::Pawn, color.pawn()),
+                (Role::Knight, color.knight()),
+                (Role::Bishop, color.bishop()),
+                (Role::Rook, color.rook()),
+                (Role::Queen, color.queen()),
+                (Role::King, color.king()),
+            ] {
+                assert_eq!(piece, Piece { color, role });
+            }
+        }
+    }
+}
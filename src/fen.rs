@@ -57,14 +57,34 @@ use core::{
 };
 
 use crate::{
-    util::AppendAscii, Bitboard, Board, ByColor, ByRole, CastlingMode, Color, EnPassantMode, File,
-    FromSetup, Piece, Position, PositionError, Rank, RemainingChecks, Role, Setup, Square,
+    util::AppendAscii, Bitboard, Board, ByColor, ByRole, Castles, CastlingMode, CastlingSide,
+    Color, EnPassantMode, File, FromSetup, Piece, Position, PositionError, Rank, RemainingChecks,
+    Role, Setup, Square,
 };
 
+#[derive(Copy, Clone)]
+#[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+enum CastlingNotation {
+    XFen,
+    Shredder,
+    Standard,
+}
+
+#[cfg(feature = "alloc")]
+fn is_standard_castling(board: &Board, castling_rights: Bitboard) -> bool {
+    Color::ALL.into_iter().all(|color| {
+        let rooks = castling_rights & color.backrank();
+        rooks.is_empty()
+            || (rooks.is_subset(Bitboard::CORNERS)
+                && board.king_of(color) == Some(Square::from_coords(File::E, color.backrank())))
+    })
+}
+
 fn append_castling<W: AppendAscii>(
     f: &mut W,
     board: &Board,
     castling_rights: Bitboard,
+    notation: CastlingNotation,
 ) -> Result<(), W::Error> {
     let mut empty = true;
 
@@ -76,16 +96,28 @@ fn append_castling<W: AppendAscii>(
         let candidates = board.by_piece(color.rook()) & color.backrank();
 
         for rook in (castling_rights & color.backrank()).into_iter().rev() {
-            f.append_ascii(
-                if Some(rook) == candidates.first() && king.map_or(false, |k| rook < k) {
-                    color.fold_wb('Q', 'q')
-                } else if Some(rook) == candidates.last() && king.map_or(false, |k| k < rook) {
-                    color.fold_wb('K', 'k')
-                } else {
+            f.append_ascii(match notation {
+                CastlingNotation::XFen
+                    if Some(rook) == candidates.first() && king.is_some_and(|k| rook < k) =>
+                {
+                    let side = CastlingSide::QueenSide;
+                    color.fold_wb(side.upper_char(), side.char())
+                }
+                CastlingNotation::XFen
+                    if Some(rook) == candidates.last() && king.is_some_and(|k| k < rook) =>
+                {
+                    let side = CastlingSide::KingSide;
+                    color.fold_wb(side.upper_char(), side.char())
+                }
+                CastlingNotation::Standard => {
+                    let side = CastlingSide::from_queen_side(rook.file() == File::A);
+                    color.fold_wb(side.upper_char(), side.char())
+                }
+                CastlingNotation::XFen | CastlingNotation::Shredder => {
                     let file = rook.file();
-                    color.fold_wb(file.char().to_ascii_uppercase(), file.char())
-                },
-            )?;
+                    color.fold_wb(file.upper_char(), file.char())
+                }
+            })?;
             empty = false;
         }
     }
@@ -113,7 +145,11 @@ fn append_pockets<W: AppendAscii>(
     f.append_ascii(']')
 }
 
-fn append_epd<W: AppendAscii>(f: &mut W, setup: &Setup) -> Result<(), W::Error> {
+fn append_epd<W: AppendAscii>(
+    f: &mut W,
+    setup: &Setup,
+    notation: CastlingNotation,
+) -> Result<(), W::Error> {
     f.reserve(21);
     setup.board.board_fen(setup.promoted).append_to(f)?;
     if let Some(ref pockets) = setup.pockets {
@@ -122,7 +158,7 @@ fn append_epd<W: AppendAscii>(f: &mut W, setup: &Setup) -> Result<(), W::Error>
     f.append_ascii(' ')?;
     f.append_ascii(setup.turn.char())?;
     f.append_ascii(' ')?;
-    append_castling(f, &setup.board, setup.castling_rights)?;
+    append_castling(f, &setup.board, setup.castling_rights, notation)?;
     f.append_ascii(' ')?;
     match setup.ep_square {
         Some(ref ep_square) => ep_square.append_to(f)?,
@@ -168,6 +204,20 @@ impl Display for ParseFenError {
 #[cfg(feature = "std")]
 impl std::error::Error for ParseFenError {}
 
+/// Error when castling rights can not be written in standard notation,
+/// because they do not correspond to the standard king and rook squares.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct NonStandardCastlingError;
+
+impl Display for NonStandardCastlingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("castling rights not expressible in standard notation")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NonStandardCastlingError {}
+
 fn parse_board_fen(board_fen: &[u8]) -> Result<(Board, Bitboard), ParseFenError> {
     let mut promoted = Bitboard(0);
     let mut board = Board::empty();
@@ -280,6 +330,34 @@ impl Display for Board {
     }
 }
 
+/// Displays the castling part of a FEN, using the notation that matches
+/// the [`CastlingMode`]: `KQkq` for standard chess, or the files of the
+/// castling rooks (Shredder-FEN, like `HAha`) for Chess960. Displays `-`
+/// if there are no castling rights.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, Position};
+///
+/// let pos = Chess::default();
+/// assert_eq!(pos.castles().to_string(), "KQkq");
+/// ```
+impl Display for Castles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Neither notation depends on the board.
+        append_castling(
+            f,
+            &Board::empty(),
+            self.castling_rights(),
+            match self.mode() {
+                CastlingMode::Standard => CastlingNotation::Standard,
+                CastlingMode::Chess960 => CastlingNotation::Shredder,
+            },
+        )
+    }
+}
+
 /// Displays a board with notation like
 /// `rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR`.
 ///
@@ -531,13 +609,59 @@ impl Fen {
     }
 
     fn append_to<W: AppendAscii>(&self, f: &mut W) -> Result<(), W::Error> {
-        append_epd(f, &self.0)?;
+        self.append_with_notation(f, CastlingNotation::XFen)
+    }
+
+    fn append_with_notation<W: AppendAscii>(
+        &self,
+        f: &mut W,
+        notation: CastlingNotation,
+    ) -> Result<(), W::Error> {
+        append_epd(f, &self.0, notation)?;
         f.append_ascii(' ')?;
         f.append_u32(self.0.halfmoves)?;
         f.append_ascii(' ')?;
         f.append_u32(u32::from(self.0.fullmoves))
     }
 
+    /// Writes the FEN with castling rights in Shredder-FEN notation, i.e.,
+    /// using the files of the castling rooks (like `HAha`), as expected by
+    /// software in Chess960 mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::fen::Fen;
+    ///
+    /// assert_eq!(
+    ///     Fen::default().to_chess960_string(),
+    ///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1"
+    /// );
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_chess960_string(&self) -> alloc::string::String {
+        let mut s = alloc::string::String::new();
+        let _ = self.append_with_notation(&mut s, CastlingNotation::Shredder);
+        s
+    }
+
+    /// Writes the FEN with castling rights in standard notation (`KQkq`),
+    /// as expected by software that does not support Chess960.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NonStandardCastlingError`] if there are castling rights
+    /// with a king not on the e-file or a rook not in a corner.
+    #[cfg(feature = "alloc")]
+    pub fn to_standard_string(&self) -> Result<alloc::string::String, NonStandardCastlingError> {
+        if !is_standard_castling(&self.0.board, self.0.castling_rights) {
+            return Err(NonStandardCastlingError);
+        }
+        let mut s = alloc::string::String::new();
+        let _ = self.append_with_notation(&mut s, CastlingNotation::Standard);
+        Ok(s)
+    }
+
     #[cfg(feature = "alloc")]
     pub fn append_to_string(&self, s: &mut alloc::string::String) {
         let _ = self.append_to(s);
@@ -619,7 +743,7 @@ impl Epd {
     }
 
     fn append_to<W: AppendAscii>(&self, f: &mut W) -> Result<(), W::Error> {
-        append_epd(f, &self.0)
+        append_epd(f, &self.0, CastlingNotation::XFen)
     }
 
     #[cfg(feature = "alloc")]
@@ -810,4 +934,92 @@ mod tests {
             "rRpppppp/8/8/8/8/8/PPPPPPBN/PPRQKBNR w KA - 0 1"
         );
     }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_castling_notation() {
+        use alloc::string::ToString as _;
+
+        use crate::Chess;
+
+        let fen: Fen = "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w KQkq - 2 9"
+            .parse()
+            .expect("valid fen");
+        assert_eq!(
+            fen.to_chess960_string(),
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"
+        );
+        assert_eq!(fen.to_standard_string(), Err(NonStandardCastlingError));
+
+        let roundtrip: Fen = fen.to_chess960_string().parse().expect("valid fen");
+        assert_eq!(roundtrip, fen);
+        let pos: Chess = roundtrip
+            .into_position(CastlingMode::Chess960)
+            .expect("legal position");
+        assert_eq!(pos.castles().castling_rights().count(), 4);
+
+        let fen: Fen = "r3k2r/8/8/8/8/8/8/R3K2R b Qk - 0 1"
+            .parse()
+            .expect("valid fen");
+        assert_eq!(
+            fen.to_chess960_string(),
+            "r3k2r/8/8/8/8/8/8/R3K2R b Ah - 0 1"
+        );
+        assert_eq!(
+            fen.to_standard_string().as_deref(),
+            Ok("r3k2r/8/8/8/8/8/8/R3K2R b Qk - 0 1")
+        );
+        for s in [fen.to_chess960_string(), fen.to_string()] {
+            assert_eq!(s.parse::<Fen>().expect("valid fen"), fen);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_castles_display() {
+        use alloc::string::ToString as _;
+
+        use crate::Chess;
+
+        for (fen, mode, expected) in [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                CastlingMode::Standard,
+                "KQkq",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                CastlingMode::Chess960,
+                "HAha",
+            ),
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1",
+                CastlingMode::Standard,
+                "Kq",
+            ),
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1",
+                CastlingMode::Chess960,
+                "Ha",
+            ),
+            (
+                "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+                CastlingMode::Chess960,
+                "HFhf",
+            ),
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", CastlingMode::Standard, "-"),
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R b - - 0 1",
+                CastlingMode::Chess960,
+                "-",
+            ),
+        ] {
+            let pos: Chess = fen
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(mode)
+                .expect("legal position");
+            assert_eq!(pos.castles().to_string(), expected, "{fen}");
+        }
+    }
 }
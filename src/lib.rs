@@ -60,6 +60,10 @@
 //! * `nohash-hasher`: Implements
 //!   [`nohash_hasher::IsEnabled`](https://docs.rs/nohash-hasher/0.2/nohash_hasher/trait.IsEnabled.html)
 //!   for sensible types.
+//! * `verify`: Asserts that every position survives a round trip through
+//!   FEN after each move played. This is very slow and intended only for
+//!   debugging state corruption. Also provides
+//!   `zobrist::verify_incremental()` to test incremental Zobrist hashing.
 
 #![no_std]
 #![doc(html_root_url = "https://docs.rs/shakmaty/0.27.2")]
@@ -76,6 +80,7 @@ extern crate std;
 #[macro_use]
 mod util;
 mod bootstrap;
+mod cached;
 mod castling_side;
 mod color;
 mod magics;
@@ -100,16 +105,23 @@ pub mod variant;
 
 pub use bitboard::Bitboard;
 pub use board::Board;
+pub use cached::CachedPosition;
 pub use castling_side::{ByCastlingSide, CastlingSide};
 pub use color::{ByColor, Color, ParseColorError};
 pub use movelist::MoveList;
 pub use perft::perft;
+#[cfg(feature = "std")]
+pub use perft::perft_tt;
+#[cfg(feature = "alloc")]
+pub use perft::{legal_lines, LegalLines};
+#[cfg(feature = "alloc")]
+pub use position::AnalysisNode;
 pub use position::{
-    Chess, FromSetup, Outcome, ParseOutcomeError, PlayError, Position, PositionError,
-    PositionErrorKinds,
+    validate_setup, Chess, FromSetup, MatePattern, Outcome, ParseOutcomeError, PlayError, Position,
+    PositionError, PositionErrorKinds, Successors, Termination, MATE_SCORE,
 };
 pub use role::{ByRole, Role};
-pub use setup::{Castles, Setup};
+pub use setup::{infer_move, Castles, Setup};
 pub use square::{File, ParseSquareError, Rank, Square};
 pub use types::{CastlingMode, EnPassantMode, Move, Piece, RemainingChecks};
 
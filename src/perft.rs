@@ -1,4 +1,16 @@
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
+
 use crate::position::Position;
+#[cfg(feature = "std")]
+use crate::zobrist::{Zobrist, ZobristValue};
+#[cfg(feature = "alloc")]
+use crate::{Move, MoveList};
 
 /// Counts legal move paths of a given length.
 ///
@@ -46,6 +58,135 @@ pub fn perft<P: Position + Clone>(pos: &P, depth: u32) -> u64 {
     }
 }
 
+/// Counts legal move paths of a given length, like [`perft()`], but
+/// memoizes the counts of subtrees in the table `tt`, keyed by Zobrist hash
+/// and remaining depth.
+///
+/// Transpositions are counted only once, which speeds up deeper perft
+/// significantly. However, in the unlikely event of a hash collision, the
+/// count of a different subtree will be reused and the result will be wrong.
+/// Use a wider [`ZobristValue`] to make this less likely.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use shakmaty::{Chess, perft_tt, zobrist::{Zobrist, Zobrist64}};
+///
+/// let pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+/// let mut tt = HashMap::new();
+/// assert_eq!(perft_tt(&pos, 3, &mut tt), 8902);
+/// ```
+#[cfg(feature = "std")]
+pub fn perft_tt<P, V, S>(pos: &Zobrist<P, V>, depth: u32, tt: &mut HashMap<(V, u32), u64, S>) -> u64
+where
+    P: Position + Clone,
+    V: ZobristValue + Hash + Eq,
+    S: BuildHasher,
+{
+    if depth < 1 {
+        1
+    } else if let Some(nodes) = tt.get(&(pos.hash(), depth)) {
+        *nodes
+    } else {
+        let moves = pos.legal_moves();
+
+        let nodes = if depth == 1 {
+            moves.len() as u64
+        } else {
+            moves
+                .iter()
+                .map(|m| {
+                    let mut child = pos.clone();
+                    child.play_unchecked(m);
+                    perft_tt(&child, depth - 1, tt)
+                })
+                .sum()
+        };
+
+        tt.insert((pos.hash(), depth), nodes);
+        nodes
+    }
+}
+
+/// Enumerates all sequences of legal moves of a given length.
+///
+/// Lines that end early (due to mate, stalemate or variant end) are
+/// included as they are. The lines are generated lazily, in depth-first
+/// order. Note that the number of lines grows exponentially with the depth.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, legal_lines, perft};
+///
+/// let pos = Chess::default();
+/// assert_eq!(legal_lines(&pos, 2).count() as u64, perft(&pos, 2));
+/// ```
+#[cfg(feature = "alloc")]
+pub fn legal_lines<P: Position + Clone>(pos: &P, depth: u32) -> LegalLines<P> {
+    LegalLines {
+        root: Some(pos.clone()),
+        depth: depth as usize,
+        stack: Vec::new(),
+        line: Vec::new(),
+    }
+}
+
+/// Iterator over sequences of legal moves, created by [`legal_lines()`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct LegalLines<P> {
+    root: Option<P>,
+    depth: usize,
+    stack: Vec<(P, MoveList, usize)>,
+    line: Vec<Move>,
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Position + Clone> Iterator for LegalLines<P> {
+    type Item = Vec<Move>;
+
+    fn next(&mut self) -> Option<Vec<Move>> {
+        if let Some(root) = self.root.take() {
+            let moves = root.legal_moves();
+            if self.depth == 0 || moves.is_empty() {
+                return Some(Vec::new());
+            }
+            self.stack.push((root, moves, 0));
+        }
+
+        // Invariant: The position of the nth stack frame is reached by the
+        // first n moves of the current line.
+        loop {
+            let (pos, moves, next) = self.stack.last_mut()?;
+            let Some(m) = moves.get(*next).copied() else {
+                self.stack.pop();
+                self.line.pop();
+                continue;
+            };
+            *next += 1;
+
+            let mut child = pos.clone();
+            child.play_unchecked(&m);
+            self.line.push(m);
+
+            let child_moves = if self.line.len() < self.depth {
+                child.legal_moves()
+            } else {
+                MoveList::new()
+            };
+            if child_moves.is_empty() {
+                let line = self.line.clone();
+                self.line.pop();
+                return Some(line);
+            }
+            self.stack.push((child, child_moves, 0));
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -57,4 +198,62 @@ mod tests {
         assert_eq!(perft(&pos, 0), 1);
         assert_eq!(perft(&pos, 1), 20);
     }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_legal_lines() {
+        use crate::{fen::Fen, CastlingMode};
+
+        let pos = Chess::default();
+        assert_eq!(legal_lines(&pos, 0).collect::<Vec<_>>(), [Vec::new()]);
+        assert_eq!(legal_lines(&pos, 1).count(), 20);
+        assert_eq!(legal_lines(&pos, 2).count() as u64, perft(&pos, 2));
+        assert_eq!(legal_lines(&pos, 3).count() as u64, perft(&pos, 3));
+        assert!(legal_lines(&pos, 2).all(|line| line.len() == 2));
+
+        for line in legal_lines(&pos, 3).step_by(97) {
+            let mut replayed = pos.clone();
+            for m in &line {
+                replayed = replayed.play(m).expect("legal");
+            }
+        }
+
+        // Mate in one and stalemate in one end lines early.
+        let pos: Chess = "6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+        let lines: Vec<_> = legal_lines(&pos, 2).collect();
+        assert_eq!(lines.iter().filter(|line| line.len() == 1).count(), 1);
+        assert_eq!(
+            lines.iter().filter(|line| line.len() == 2).count() as u64,
+            perft(&pos, 2)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_perft_tt() {
+        use std::collections::HashMap;
+
+        use crate::{
+            fen::Fen,
+            zobrist::{Zobrist, Zobrist64},
+            CastlingMode,
+        };
+
+        let pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+        let mut tt = HashMap::new();
+        assert_eq!(perft_tt(&pos, 5, &mut tt), 4_865_609);
+
+        let pos: Zobrist<Chess, Zobrist64> =
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+        let mut tt = HashMap::new();
+        assert_eq!(perft_tt(&pos, 3, &mut tt), perft(&pos.into_inner(), 3));
+    }
 }
@@ -16,6 +16,13 @@ use crate::{
     EnPassantMode, Move, MoveList, Piece, Rank, RemainingChecks, Role, Square,
 };
 
+/// Score of a checkmate at the root, as used by
+/// [`Position::terminal_value()`].
+///
+/// Mate scores are adjusted by the distance from the root, so that shorter
+/// mates are preferred.
+pub const MATE_SCORE: i32 = 32_000;
+
 /// Outcome of a game.
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum Outcome {
@@ -92,6 +99,82 @@ impl FromStr for Outcome {
     }
 }
 
+/// Reason for the end of a game, as determined by
+/// `Position::termination()` (requires `alloc`).
+#[non_exhaustive]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Termination {
+    /// The side to move is checkmated. Contains the winner.
+    Checkmate(Color),
+    /// The side to move has no legal moves, but is not in check.
+    Stalemate,
+    /// Neither side has sufficient material to win.
+    InsufficientMaterial,
+    /// No capture or pawn move in the last 50 moves.
+    FiftyMove,
+    /// The position occurred the given number of times, at least three.
+    Repetition(u8),
+    /// The game ended due to a variant specific rule.
+    VariantEnd(Outcome),
+}
+
+/// Common checkmate patterns, as classified by [`Position::mate_pattern()`].
+#[non_exhaustive]
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum MatePattern {
+    /// A rook or queen mates the king on its backrank, where it is trapped
+    /// by its own pieces.
+    BackRank,
+    /// A knight mates the king, which is completely surrounded by its own
+    /// pieces.
+    Smothered,
+}
+
+/// Snapshot of commonly queried information about a position, as
+/// computed by [`Position::analysis_node()`].
+#[cfg(feature = "alloc")]
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct AnalysisNode {
+    /// Zobrist hash, as computed by
+    /// [`ZobristHash::zobrist_hash()`](crate::zobrist::ZobristHash::zobrist_hash())
+    /// with [`EnPassantMode::Legal`].
+    pub hash: crate::zobrist::Zobrist64,
+    /// FEN, with [`EnPassantMode::Legal`].
+    pub fen: alloc::string::String,
+    /// Legal moves.
+    pub legal_moves: MoveList,
+    /// Outcome of the game, if any.
+    pub outcome: Option<Outcome>,
+}
+
+/// Iterator over the legal moves of a position, each paired with the
+/// position after playing it, created by [`Position::successors()`].
+#[derive(Clone, Debug)]
+pub struct Successors<P> {
+    pos: P,
+    moves: MoveList,
+    next: usize,
+}
+
+impl<P: Position + Clone> Iterator for Successors<P> {
+    type Item = (Move, P);
+
+    fn next(&mut self) -> Option<(Move, P)> {
+        let m = *self.moves.get(self.next)?;
+        self.next += 1;
+        let mut child = self.pos.clone();
+        child.play_unchecked(&m);
+        Some((m, child))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.moves.len() - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<P: Position + Clone> ExactSizeIterator for Successors<P> {}
+
 /// Error when trying to play an illegal move.
 #[derive(Debug)]
 pub struct PlayError<P> {
@@ -300,6 +383,40 @@ impl<P> fmt::Display for PositionError<P> {
 #[cfg(feature = "std")]
 impl<P> std::error::Error for PositionError<P> {}
 
+/// Checks if `setup` is a legal position of standard chess, without keeping
+/// the resulting position. Useful for validating positions in an editor, as
+/// they are being edited.
+///
+/// Performs the same checks as [`Chess::from_setup()`].
+///
+/// # Errors
+///
+/// Returns all [`PositionErrorKinds`] that apply to `setup`.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{validate_setup, Board, CastlingMode, PositionErrorKinds, Setup};
+///
+/// assert_eq!(validate_setup(&Setup::initial(), CastlingMode::Standard), Ok(()));
+///
+/// let setup = Setup {
+///     board: Board::empty(),
+///     ..Setup::initial()
+/// };
+/// assert!(validate_setup(&setup, CastlingMode::Standard)
+///     .unwrap_err()
+///     .contains(PositionErrorKinds::EMPTY_BOARD));
+/// ```
+pub fn validate_setup(setup: &Setup, mode: CastlingMode) -> Result<(), PositionErrorKinds> {
+    let (_, _, _, errors) = Chess::from_setup_unchecked(setup.clone(), mode);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
 /// Validate and set up a playable [`Position`]. All provided chess variants
 /// support this.
 pub trait FromSetup: Sized {
@@ -328,6 +445,44 @@ pub trait FromSetup: Sized {
     /// # Ok::<_, PositionError<_>>(())
     /// ```
     fn from_setup(setup: Setup, mode: CastlingMode) -> Result<Self, PositionError<Self>>;
+
+    /// Set up a playable [`Position`] from just the piece positions and the
+    /// side to move. The position will have no castling rights and no
+    /// en passant square.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PositionError`] if the resulting [`Setup`] does not meet
+    /// [basic validity requirements](PositionErrorKinds).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Board, CastlingMode, Chess, Color, FromSetup, Position, PositionError};
+    ///
+    /// let board: Board = "4k3/8/8/8/8/8/4P3/4K3".parse()?;
+    /// let pos = Chess::from_board(board, Color::White, CastlingMode::Standard)?;
+    /// assert_eq!(pos.legal_moves().len(), 6);
+    ///
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<shakmaty::fen::ParseFenError> for CommonError { fn from(_: shakmaty::fen::ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    fn from_board(
+        board: Board,
+        turn: Color,
+        mode: CastlingMode,
+    ) -> Result<Self, PositionError<Self>> {
+        Self::from_setup(
+            Setup {
+                board,
+                turn,
+                ..Setup::empty()
+            },
+            mode,
+        )
+    }
 }
 
 /// A playable chess or chess variant position. See [`Chess`] for a concrete
@@ -380,6 +535,21 @@ pub trait Position {
     fn into_setup(self, mode: EnPassantMode) -> Setup;
 
     /// Generates all legal moves.
+    ///
+    /// The moves are generated in a deterministic order that depends only on
+    /// the position, so that they can be indexed consistently, for example
+    /// when generating opening books. Changes to the order are considered
+    /// breaking changes.
+    ///
+    /// For [`Chess`], en passant captures come first. Then, when not in
+    /// check, pawn moves are generated (captures before single steps before
+    /// double steps, with promotions to queen, rook, bishop, and knight in
+    /// turn), followed by knight, bishop, rook, queen, and king moves, and
+    /// finally castling, king side before queen side. When in check, only
+    /// evasions follow: king moves first, then captures of the checker and
+    /// blocks in the same piece order as above. Within each group, squares
+    /// are visited from a1 to h8. The provided variants use the same scheme,
+    /// with their own additions (such as drops) generated last.
     fn legal_moves(&self) -> MoveList;
 
     /// Generates a subset of legal moves: All piece moves and drops of type
@@ -393,7 +563,7 @@ pub trait Position {
     /// Generates legal castling moves.
     fn castling_moves(&self, side: CastlingSide) -> MoveList {
         let mut moves = self.legal_moves();
-        moves.retain(|m| m.castling_side().map_or(false, |s| side == s));
+        moves.retain(|m| m.castling_side() == Some(side));
         moves
     }
 
@@ -531,6 +701,100 @@ pub trait Position {
         moves.contains(m)
     }
 
+    /// Generates legal moves, except for the moves in `excluded`.
+    ///
+    /// Useful to search alternative root moves, for example to find
+    /// multiple principal variations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Move, Position, Role, Square};
+    ///
+    /// let pos = Chess::default();
+    /// let e4 = Move::Normal {
+    ///     role: Role::Pawn,
+    ///     from: Square::E2,
+    ///     capture: None,
+    ///     to: Square::E4,
+    ///     promotion: None,
+    /// };
+    ///
+    /// let moves = pos.legal_moves_excluding(&[e4.clone()]);
+    /// assert_eq!(moves.len(), 19);
+    /// assert!(!moves.contains(&e4));
+    /// ```
+    fn legal_moves_excluding(&self, excluded: &[Move]) -> MoveList /* FINAL */ {
+        let mut moves = self.legal_moves();
+        moves.retain(|m| !excluded.contains(m));
+        moves
+    }
+
+    /// Iterates over the legal moves, each paired with the position after
+    /// playing it.
+    ///
+    /// The position is cloned for each move. Wrappers like
+    /// [`Zobrist`](crate::zobrist::Zobrist) are cloned as well, so that each
+    /// successor carries its incrementally updated state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Position};
+    ///
+    /// let pos = Chess::default();
+    /// for (m, child) in pos.successors() {
+    ///     assert_eq!(child.board().piece_at(m.to()), Some(m.role().of(pos.turn())));
+    /// }
+    /// assert_eq!(pos.successors().len(), 20);
+    /// ```
+    fn successors(&self) -> Successors<Self>
+    where
+        Self: Sized + Clone,
+    {
+        Successors {
+            pos: self.clone(),
+            moves: self.legal_moves(),
+            next: 0,
+        }
+    }
+
+    /// Tests if `color` can castle to `side` right now.
+    ///
+    /// Unlike [`Castles::has()`], which only tests the castling right (and
+    /// is what Zobrist hashes and FENs are based on), this requires that
+    /// it is `color`'s turn, the path is clear, and the king is not in
+    /// check, does not pass through check, and does not end up in check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, CastlingSide, Chess, Color, Position};
+    ///
+    /// let pos: Chess = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// assert!(pos.can_castle(Color::White, CastlingSide::KingSide));
+    ///
+    /// // Black has the right, but it is not black's turn.
+    /// assert!(pos.castles().has(Color::Black, CastlingSide::KingSide));
+    /// assert!(!pos.can_castle(Color::Black, CastlingSide::KingSide));
+    ///
+    /// // The path is blocked.
+    /// assert!(!Chess::default().can_castle(Color::White, CastlingSide::KingSide));
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    fn can_castle(&self, color: Color, side: CastlingSide) -> bool /* FINAL */ {
+        color == self.turn()
+            && self.castles().has(color, side)
+            && !self.castling_moves(side).is_empty()
+    }
+
     /// The en passant square, if it is the target of a
     /// [pseudo-legal](`EnPassantMode::PseudoLegal`) en passant move.
     fn pseudo_legal_ep_square(&self) -> Option<Square> /* FINAL */ {
@@ -568,6 +832,458 @@ pub trait Position {
         self.checkers().any()
     }
 
+    /// Finds the least valuable piece of `attacker` that attacks `square`.
+    ///
+    /// Only pieces in `occupied` are considered, and sliding pieces attack
+    /// through squares missing from `occupied`. Removing pieces from
+    /// `occupied` one by one reveals x-ray attackers, as needed for static
+    /// exchange evaluation.
+    fn least_valuable_attacker(
+        &self,
+        square: Square,
+        attacker: Color,
+        occupied: Bitboard,
+    ) -> Option<(Square, Role)> /* FINAL */ {
+        least_valuable_attacker(self.board(), square, attacker, occupied)
+    }
+
+    /// Tests if the static exchange evaluation of `m` is at least
+    /// `threshold`, i.e., if the sequence of captures on the target square,
+    /// each side always recapturing with its least valuable piece, gains at
+    /// least `threshold` centipawns for the moving side. Either side may stop
+    /// capturing when it is not favorable.
+    ///
+    /// Uses the material values of [`Role::value()`]. Pins are not
+    /// considered.
+    /// Moves other than normal moves are assumed to be neutral.
+    ///
+    /// This terminates early, as soon as the outcome relative to the
+    /// threshold is decided.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, Chess, Move, Position, Role, Square};
+    ///
+    /// let pos: Chess = "4k3/8/2p5/3p4/8/8/8/3RK3 w - - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// let rxd5 = Move::Normal {
+    ///     role: Role::Rook,
+    ///     from: Square::D1,
+    ///     capture: Some(Role::Pawn),
+    ///     to: Square::D5,
+    ///     promotion: None,
+    /// };
+    /// assert!(pos.see_ge(&rxd5, -400)); // Rook for pawn
+    /// assert!(!pos.see_ge(&rxd5, 0));
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    fn see_ge(&self, m: &Move, threshold: i32) -> bool /* FINAL */ {
+        let Move::Normal {
+            role,
+            from,
+            capture,
+            to,
+            ..
+        } = *m
+        else {
+            return 0 >= threshold;
+        };
+
+        let mut swap = capture.map_or(0, Role::value) - threshold;
+        if swap < 0 {
+            return false;
+        }
+
+        swap = role.value() - swap;
+        if swap <= 0 {
+            return true;
+        }
+
+        let mut result = true;
+        let occupied = self.board().occupied() ^ from ^ to;
+        for (_, role) in Exchanges::new(self.board(), to, !self.turn(), occupied) {
+            result = !result;
+            swap = role.value() - swap;
+            if swap < i32::from(result) {
+                break;
+            }
+        }
+
+        result
+    }
+
+    /// The sequence of captures on `square` considered by static exchange
+    /// evaluation, starting with the side to move, as pairs of the square
+    /// and role of the capturing piece.
+    ///
+    /// Both sides alternate capturing with their least valuable attacker,
+    /// revealing x-ray attackers as pieces leave. The sequence ends as soon
+    /// as the side to capture would lose material by continuing the
+    /// exchange, so it is empty if the side to move should not start it.
+    /// As in [`Position::see_ge()`], pins are not considered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, Chess, Position, Role, Square};
+    ///
+    /// let pos: Chess = "3rk3/8/2p5/3n4/8/4N3/8/3RK3 w - - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// assert_eq!(
+    ///     pos.exchange_sequence(Square::D5),
+    ///     [(Square::E3, Role::Knight), (Square::C6, Role::Pawn)]
+    /// );
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn exchange_sequence(&self, square: Square) -> alloc::vec::Vec<(Square, Role)> /* FINAL */ {
+        let board = self.board();
+        let mut captures = alloc::vec::Vec::new();
+        let mut values = alloc::vec::Vec::new();
+        let mut victim = board.role_at(square).map_or(0, Role::value);
+        for (from, role) in Exchanges::new(board, square, self.turn(), board.occupied()) {
+            captures.push((from, role));
+            values.push(victim);
+            victim = role.value();
+        }
+
+        // Minimax from the end of the sequence: A side only captures if it
+        // does not lose material, given the best continuation.
+        let mut scores = values;
+        for i in (0..scores.len().saturating_sub(1)).rev() {
+            scores[i] -= scores[i + 1].max(0);
+        }
+        let len = scores.iter().take_while(|&&score| score >= 0).count();
+        captures.truncate(len);
+        captures
+    }
+
+    /// The material of white minus the material of black in centipawns,
+    /// using the values of [`Role::value()`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Position};
+    ///
+    /// assert_eq!(Chess::default().material_balance(), 0);
+    /// ```
+    fn material_balance(&self) -> i32 /* FINAL */ {
+        let board = self.board();
+        Role::ALL
+            .into_iter()
+            .map(|role| {
+                let white = (board.by_role(role) & board.white()).count() as i32;
+                let black = (board.by_role(role) & board.black()).count() as i32;
+                role.value() * (white - black)
+            })
+            .sum()
+    }
+
+    /// Estimates the game phase for tapered evaluation, from `256` in the
+    /// opening to `0` in the endgame.
+    ///
+    /// The phase is based on the remaining non-pawn material of both sides,
+    /// with the usual weights of 1 for knights and bishops, 2 for rooks,
+    /// and 4 for queens. The starting material has a total weight of 24.
+    /// Promotions can not push the phase above `256`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Position};
+    ///
+    /// assert_eq!(Chess::default().game_phase(), 256);
+    /// ```
+    fn game_phase(&self) -> u16 /* FINAL */ {
+        const TOTAL: u32 = 24;
+
+        let board = self.board();
+        let weight = (board.knights() | board.bishops()).count() as u32
+            + 2 * board.rooks().count() as u32
+            + 4 * board.queens().count() as u32;
+        (weight.min(TOTAL) * 256 / TOTAL) as u16
+    }
+
+    /// The square of the king of `color` and all adjacent squares, often
+    /// called the king ring. Empty if there is no king of `color`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Color, Position};
+    ///
+    /// let pos = Chess::default();
+    /// assert_eq!(pos.king_safety_zone(Color::White).count(), 6);
+    /// ```
+    fn king_safety_zone(&self, color: Color) -> Bitboard /* FINAL */ {
+        self.board().king_of(color).map_or(Bitboard::EMPTY, |king| {
+            attacks::king_attacks(king).with(king)
+        })
+    }
+
+    /// All squares attacked by the pieces of `color`, given the current
+    /// occupancy. Pawns attack diagonally, not forward.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, Chess, Color, Position, Rank, Square};
+    ///
+    /// let pos = Chess::default();
+    /// let attacked = pos.attacked_squares(Color::White);
+    /// assert!(attacked.contains(Square::F3));
+    /// assert!((attacked & Bitboard::from_rank(Rank::Fourth)).is_empty());
+    /// ```
+    fn attacked_squares(&self, color: Color) -> Bitboard /* FINAL */ {
+        let board = self.board();
+        board
+            .by_color(color)
+            .into_iter()
+            .fold(Bitboard::EMPTY, |attacked, sq| {
+                attacked | board.attacks_from(sq)
+            })
+    }
+
+    /// Counts the pseudo-legal moves of each role of `color`, a common
+    /// mobility term in evaluation functions.
+    ///
+    /// Computed directly from attack bitboards, without generating moves.
+    /// Each promotion counts as four moves, like in a move list. Castling
+    /// and en passant captures are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Color, Position};
+    ///
+    /// let pos = Chess::default();
+    /// let mobility = pos.mobility(Color::White);
+    /// assert_eq!(mobility.pawn, 16);
+    /// assert_eq!(mobility.knight, 4);
+    /// assert_eq!(mobility.bishop, 0);
+    /// ```
+    fn mobility(&self, color: Color) -> ByRole<u32> /* FINAL */ {
+        let board = self.board();
+        let ours = board.by_color(color);
+        let theirs = board.by_color(!color);
+
+        // Promotions are counted once for each promotion role.
+        let count = |targets: Bitboard| {
+            (targets & !Bitboard::BACKRANKS).count() as u32
+                + 4 * (targets & Bitboard::BACKRANKS).count() as u32
+        };
+
+        let mut mobility = ByRole::<u32>::default();
+
+        let pawns = board.pawns() & ours;
+        let single_moves = pawns.shift(color.fold_wb(8, -8)) & !board.occupied();
+        let double_moves = single_moves.shift(color.fold_wb(8, -8))
+            & color.fold_wb(Bitboard::SOUTH, Bitboard::NORTH)
+            & !board.occupied();
+        mobility.pawn = count(single_moves) + count(double_moves);
+        for sq in pawns {
+            mobility.pawn += count(attacks::pawn_attacks(color, sq) & theirs);
+        }
+
+        for sq in ours & !board.pawns() {
+            if let Some(role) = board.role_at(sq) {
+                *mobility.get_mut(role) += (board.attacks_from(sq) & !ours).count() as u32;
+            }
+        }
+
+        mobility
+    }
+
+    /// Tests if the legal move `m` would put the opponent in check.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, Chess, Move, Position, Role, Square};
+    ///
+    /// let pos: Chess = "4k3/8/8/8/8/8/8/R3K3 w - - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// let ra8 = Move::Normal {
+    ///     role: Role::Rook,
+    ///     from: Square::A1,
+    ///     capture: None,
+    ///     to: Square::A8,
+    ///     promotion: None,
+    /// };
+    /// assert!(pos.gives_check(&ra8));
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    #[rustfmt::skip] // Keep the marker after the return type.
+    fn gives_check(&self, m: &Move) -> bool /* FINAL */
+    where
+        Self: Sized + Clone,
+    {
+        let mut pos = self.clone();
+        pos.play_unchecked(m);
+        pos.is_check()
+    }
+
+    /// Tests if capturing en passant from `from` to `to` is legal.
+    ///
+    /// Besides checking the en passant square, this correctly handles the
+    /// case where removing both the capturing and the captured pawn from the
+    /// same rank exposes the king to a rook or queen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, Chess, Position, Square};
+    ///
+    /// let pos: Chess = "8/8/8/KPp4r/8/8/8/7k w - c6 0 2"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// assert!(!pos.is_legal_en_passant(Square::B5, Square::C6));
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    fn is_legal_en_passant(&self, from: Square, to: Square) -> bool /* FINAL */ {
+        self.en_passant_moves()
+            .iter()
+            .any(|m| m.from() == Some(from) && m.to() == to)
+    }
+
+    /// Determines if the origin square of the legal move `m` must be
+    /// disambiguated in SAN, considering only the other pieces of the same
+    /// role that can legally move to the same square.
+    ///
+    /// Pawn moves, castling moves, and drops never need disambiguation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, san::Disambiguation, CastlingMode, Chess, Move, Position, Role, Square};
+    ///
+    /// let pos: Chess = "4k3/8/8/8/8/8/8/R4RK1 w - - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// let rad1 = Move::Normal {
+    ///     role: Role::Rook,
+    ///     from: Square::A1,
+    ///     capture: None,
+    ///     to: Square::D1,
+    ///     promotion: None,
+    /// };
+    /// assert_eq!(pos.needs_disambiguation(&rad1), Disambiguation::File);
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    fn needs_disambiguation(&self, m: &Move) -> crate::san::Disambiguation {
+        use crate::san::{disambiguation, Disambiguation};
+
+        match *m {
+            Move::Normal {
+                role,
+                from,
+                to,
+                promotion,
+                ..
+            } if role != Role::Pawn => {
+                disambiguation(role, from, to, promotion, &self.san_candidates(role, to))
+            }
+            _ => Disambiguation::None,
+        }
+    }
+
+    /// Tests if the side to move has no captures, promotions, or checking
+    /// moves available. Quiescence search can stop in such quiet positions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, Chess, Position};
+    ///
+    /// let pos = Chess::default();
+    /// assert!(pos.is_quiet());
+    ///
+    /// let pos: Chess = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// assert!(!pos.is_quiet());
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    #[rustfmt::skip] // Keep the marker after the return type.
+    fn is_quiet(&self) -> bool /* FINAL */
+    where
+        Self: Sized + Clone,
+    {
+        self.capture_moves().is_empty()
+            && self.promotion_moves().is_empty()
+            && !self.legal_moves().iter().any(|m| self.gives_check(m))
+    }
+
+    /// Tests if the piece on `square` is attacked by more opponent pieces
+    /// than it is defended by.
+    ///
+    /// This is only a quick heuristic: it does not consider the values of
+    /// the pieces involved, pins, or x-ray attacks. Returns `false` if
+    /// `square` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, Chess, Position, Square};
+    ///
+    /// let pos: Chess = "4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    ///
+    /// assert!(pos.is_hanging(Square::D5));
+    /// assert!(pos.is_hanging(Square::E4));
+    /// assert!(!pos.is_hanging(Square::E1));
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    fn is_hanging(&self, square: Square) -> bool /* FINAL */ {
+        self.board().color_at(square).is_some_and(|color| {
+            let occupied = self.board().occupied();
+            let attackers = self.board().attacks_to(square, !color, occupied);
+            let defenders = self.board().attacks_to(square, color, occupied);
+            attackers.count() > defenders.count()
+        })
+    }
+
     /// Tests for checkmate.
     fn is_checkmate(&self) -> bool /* FINAL */ {
         !self.checkers().is_empty() && self.legal_moves().is_empty()
@@ -594,30 +1310,449 @@ pub trait Position {
 
     /// The outcome of the game, or `None` if the game is not over.
     fn outcome(&self) -> Option<Outcome> /* FINAL */ {
-        self.variant_outcome().or_else(|| {
-            if self.legal_moves().is_empty() {
-                Some(if self.is_check() {
-                    Outcome::Decisive {
-                        winner: !self.turn(),
-                    }
-                } else {
-                    Outcome::Draw // Stalemate
-                })
-            } else if self.is_insufficient_material() {
-                Some(Outcome::Draw)
-            } else {
-                None
-            }
-        })
+        self.variant_outcome()
+            .or_else(|| outcome_with_legal_moves(self, &self.legal_moves()))
     }
 
-    /// Plays a move.
+    /// Scores a game that is over from the point of view of the side to
+    /// move, for use at the leaves of a search. Returns `None` if the game
+    /// is not over.
     ///
+    /// A loss is scored as `-MATE_SCORE + ply` and a win as
+    /// `MATE_SCORE - ply`, where `ply` is the distance from the root of the
+    /// search, so that faster wins and slower losses are preferred.
+    /// Draws are scored as `0`.
     ///
-    /// # Errors
+    /// # Examples
     ///
-    /// Returns a [`PlayError`] if the move is not legal. You can use
-    /// [`Position::play_unchecked()`] if you can guarantee legality.
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, Chess, Position, MATE_SCORE};
+    ///
+    /// let pos: Chess = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// assert_eq!(pos.terminal_value(4), Some(-MATE_SCORE + 4));
+    ///
+    /// assert_eq!(Chess::default().terminal_value(0), None);
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    fn terminal_value(&self, ply: u32) -> Option<i32> /* FINAL */ {
+        let ply = ply.min(MATE_SCORE as u32) as i32;
+        self.outcome().map(|outcome| match outcome.winner() {
+            Some(winner) if winner == self.turn() => MATE_SCORE - ply,
+            Some(_) => -MATE_SCORE + ply,
+            None => 0,
+        })
+    }
+
+    /// Classifies the [checkmate](Position::is_checkmate()), if it matches
+    /// one of the common [`MatePattern`]s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, Chess, MatePattern, Position};
+    ///
+    /// let pos: Chess = "6rk/5Npp/8/8/8/8/8/6K1 b - - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// assert_eq!(pos.mate_pattern(), Some(MatePattern::Smothered));
+    ///
+    /// assert_eq!(Chess::default().mate_pattern(), None);
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    fn mate_pattern(&self) -> Option<MatePattern> /* FINAL */ {
+        if !self.is_checkmate() {
+            return None;
+        }
+
+        let king = self.board().king_of(self.turn())?;
+        let checkers = self.checkers();
+        let escapes = attacks::king_attacks(king) & !self.us();
+
+        if escapes.is_empty() && (checkers & !self.board().knights()).is_empty() {
+            Some(MatePattern::Smothered)
+        } else if king.rank() == self.turn().backrank()
+            && (escapes & !Bitboard::from_rank(king.rank())).is_empty()
+            && (checkers & !self.board().rooks_and_queens()).is_empty()
+            && (checkers & !Bitboard::from_rank(king.rank())).is_empty()
+        {
+            Some(MatePattern::BackRank)
+        } else {
+            None
+        }
+    }
+
+    /// Tests for draws that end the game automatically, without a claim:
+    /// [stalemate](Position::is_stalemate()),
+    /// [insufficient material](Position::is_insufficient_material()),
+    /// the 75-move rule, and, if `repetitions` are given, fivefold
+    /// repetition.
+    ///
+    /// The [`RepetitionTable`](crate::zobrist::RepetitionTable) must include
+    /// the current position.
+    #[cfg(feature = "alloc")]
+    fn is_draw(&self, repetitions: Option<&crate::zobrist::RepetitionTable>) -> bool
+    where
+        Self: Sized,
+    {
+        use crate::zobrist::ZobristHash as _;
+
+        self.is_stalemate()
+            || self.is_insufficient_material()
+            || (self.halfmoves() >= 150 && !self.is_checkmate())
+            || repetitions
+                .is_some_and(|table| table.count(self.zobrist_hash(EnPassantMode::Legal)) >= 5)
+    }
+
+    /// Gets the reason for the end of the game, if any.
+    ///
+    /// Unlike [`Position::outcome()`] and [`Position::is_draw()`], this also
+    /// reports draws that can merely be claimed: by the fifty-move rule,
+    /// and, if `repetitions` are given, by threefold repetition.
+    ///
+    /// The [`RepetitionTable`](crate::zobrist::RepetitionTable) must include
+    /// the current position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, Chess, Color, Position, Termination};
+    ///
+    /// assert_eq!(Chess::default().termination(None), None);
+    ///
+    /// let pos: Chess = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// assert_eq!(pos.termination(None), Some(Termination::Checkmate(Color::Black)));
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn termination(
+        &self,
+        repetitions: Option<&crate::zobrist::RepetitionTable>,
+    ) -> Option<Termination>
+    where
+        Self: Sized,
+    {
+        use crate::zobrist::ZobristHash as _;
+
+        if let Some(outcome) = self.variant_outcome() {
+            return Some(Termination::VariantEnd(outcome));
+        }
+
+        if self.legal_moves().is_empty() {
+            return Some(if self.is_check() {
+                Termination::Checkmate(!self.turn())
+            } else {
+                Termination::Stalemate
+            });
+        }
+
+        if self.is_insufficient_material() {
+            Some(Termination::InsufficientMaterial)
+        } else if self.halfmoves() >= 100 {
+            Some(Termination::FiftyMove)
+        } else {
+            repetitions
+                .map(|table| table.count(self.zobrist_hash(EnPassantMode::Legal)))
+                .filter(|&count| count >= 3)
+                .map(|count| Termination::Repetition(u8::try_from(count).unwrap_or(u8::MAX)))
+        }
+    }
+
+    /// Tests if the side to move can claim a draw, as opposed to automatic
+    /// draws as tested by [`Position::is_draw()`].
+    ///
+    /// A draw can be claimed by the fifty-move rule, or by threefold
+    /// repetition, including if the side to move is about to play a move
+    /// that completes the fifty moves or repeats a position for the third
+    /// time.
+    ///
+    /// The [`RepetitionTable`](crate::zobrist::RepetitionTable) must include
+    /// the current position.
+    #[cfg(feature = "alloc")]
+    fn can_claim_draw(&self, history: &crate::zobrist::RepetitionTable) -> bool
+    where
+        Self: Sized + Clone,
+    {
+        use crate::zobrist::ZobristHash as _;
+
+        self.halfmoves() >= 100
+            || history.count(self.zobrist_hash(EnPassantMode::Legal)) >= 3
+            || self.legal_moves().iter().any(|m| {
+                let mut after = self.clone();
+                after.play_unchecked(m);
+                after.halfmoves() >= 100
+                    || history.count(after.zobrist_hash(EnPassantMode::Legal)) >= 2
+            })
+    }
+
+    /// Generates all legal moves, each with its SAN (including check and
+    /// checkmate suffixes) and UCI notation, as needed for move lists in user
+    /// interfaces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Position};
+    ///
+    /// let moves = Chess::default().root_moves();
+    /// assert_eq!(moves.len(), 20);
+    ///
+    /// let (_, san, uci) = &moves[0];
+    /// assert_eq!(san, "a3");
+    /// assert_eq!(uci, "a2a3");
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn root_moves(&self) -> alloc::vec::Vec<(Move, alloc::string::String, alloc::string::String)>
+    where
+        Self: Sized + Clone,
+    {
+        use alloc::string::ToString as _;
+
+        use crate::san::{San, SanPlus, Suffix};
+
+        let moves = self.legal_moves();
+        moves
+            .iter()
+            .map(|m| {
+                let mut after = self.clone();
+                after.play_unchecked(m);
+                let san = SanPlus {
+                    san: San::disambiguate(m, &moves),
+                    suffix: Suffix::from_position(&after),
+                };
+                let uci = m.to_uci(self.castles().mode());
+                (*m, san.to_string(), uci.to_string())
+            })
+            .collect()
+    }
+
+    /// The material signature of the position, as used to name Syzygy
+    /// tablebase files, like `KQvKR`.
+    ///
+    /// Pieces are listed from king to pawn. The side with more pieces comes
+    /// first, with ties broken in favor of the side with stronger pieces, so
+    /// that color swapped positions have the same signature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, Chess, Position};
+    ///
+    /// let pos: Chess = "8/8/2k5/8/3r4/8/1Q6/4K3 w - - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// assert_eq!(pos.material_signature(), "KQvKR");
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn material_signature(&self) -> alloc::string::String /* FINAL */ {
+        let material = self.board().material();
+        let side = |color: Color| -> alloc::vec::Vec<Role> {
+            Role::ALL
+                .into_iter()
+                .rev()
+                .flat_map(|role| {
+                    core::iter::repeat(role).take(usize::from(*material.get(color).get(role)))
+                })
+                .collect()
+        };
+        let (white, black) = (side(White), side(Black));
+
+        // Stronger roles first, as in the naming of Syzygy tables.
+        let strength = |roles: &[Role]| -> alloc::vec::Vec<u8> {
+            roles
+                .iter()
+                .map(|&role| Role::King as u8 - role as u8)
+                .collect()
+        };
+        let (first, second) = if (white.len(), strength(&black)) < (black.len(), strength(&white)) {
+            (black, white)
+        } else {
+            (white, black)
+        };
+
+        let mut signature = alloc::string::String::new();
+        signature.extend(first.iter().map(|role| role.upper_char()));
+        signature.push('v');
+        signature.extend(second.iter().map(|role| role.upper_char()));
+        signature
+    }
+
+    /// Renders a short human readable summary of the side to move, the move
+    /// counters, and check or outcome, for logging.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, Chess, Position};
+    ///
+    /// let pos = Chess::default();
+    /// assert_eq!(pos.status_line(), "White to move, halfmove 0, fullmove 1");
+    ///
+    /// let pos: Chess = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// assert_eq!(
+    ///     pos.status_line(),
+    ///     "White to move, halfmove 1, fullmove 3, White checkmated, 0-1"
+    /// );
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn status_line(&self) -> alloc::string::String /* FINAL */ {
+        use core::fmt::Write as _;
+
+        let turn = self.turn().fold_wb("White", "Black");
+        let mut line = alloc::format!(
+            "{} to move, halfmove {}, fullmove {}",
+            turn,
+            self.halfmoves(),
+            self.fullmoves()
+        );
+        if self.is_checkmate() {
+            let _ = write!(line, ", {turn} checkmated");
+        } else if self.is_check() {
+            let _ = write!(line, ", {turn} in check");
+        } else if self.is_stalemate() {
+            line.push_str(", stalemate");
+        }
+        if let Some(outcome) = self.outcome() {
+            let _ = write!(line, ", {outcome}");
+        }
+        line
+    }
+
+    /// Renders the board with Unicode chess symbols, for command line tools.
+    /// The origin and target squares of `last_move` are highlighted with
+    /// brackets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Move, Position, Role, Square};
+    ///
+    /// let e4 = Move::Normal {
+    ///     role: Role::Pawn,
+    ///     from: Square::E2,
+    ///     capture: None,
+    ///     to: Square::E4,
+    ///     promotion: None,
+    /// };
+    /// let pos = Chess::default().play(&e4)?;
+    /// assert_eq!(
+    ///     pos.pretty(Some(&e4)),
+    ///     "8  ♜  ♞  ♝  ♛  ♚  ♝  ♞  ♜\n\
+    ///      7  ♟  ♟  ♟  ♟  ♟  ♟  ♟  ♟\n\
+    ///      6  .  .  .  .  .  .  .  .\n\
+    ///      5  .  .  .  .  .  .  .  .\n\
+    ///      4  .  .  .  . [♙] .  .  .\n\
+    ///      3  .  .  .  .  .  .  .  .\n\
+    ///      2  ♙  ♙  ♙  ♙ [.] ♙  ♙  ♙\n\
+    ///      1  ♖  ♘  ♗  ♕  ♔  ♗  ♘  ♖\n\
+    ///      \x20  a  b  c  d  e  f  g  h\n"
+    /// );
+    /// # Ok::<_, shakmaty::PlayError<_>>(())
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn pretty(&self, last_move: Option<&Move>) -> alloc::string::String /* FINAL */ {
+        use crate::File;
+
+        let highlighted = last_move.map_or(Bitboard::EMPTY, |m| {
+            Bitboard::from(m.to()) | m.from().map_or(Bitboard::EMPTY, Bitboard::from)
+        });
+
+        let mut pretty = alloc::string::String::new();
+        for rank in Rank::ALL.into_iter().rev() {
+            pretty.push(rank.char());
+            pretty.push(' ');
+            for file in File::ALL {
+                let square = Square::from_coords(file, rank);
+                let symbol = self.board().piece_at(square).map_or('.', Piece::figurine);
+                if highlighted.contains(square) {
+                    pretty.push('[');
+                    pretty.push(symbol);
+                    pretty.push(']');
+                } else {
+                    pretty.push(' ');
+                    pretty.push(symbol);
+                    pretty.push(' ');
+                }
+            }
+            pretty.truncate(pretty.trim_end().len());
+            pretty.push('\n');
+        }
+        pretty.push_str("   a  b  c  d  e  f  g  h\n");
+        pretty
+    }
+
+    /// Computes an [`AnalysisNode`] with the hash, FEN, legal moves, and
+    /// outcome of the position, for example to build analysis trees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Position};
+    ///
+    /// let node = Chess::default().analysis_node();
+    /// assert_eq!(node.fen, "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    /// assert_eq!(node.legal_moves.len(), 20);
+    /// assert_eq!(node.outcome, None);
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn analysis_node(&self) -> AnalysisNode
+    where
+        Self: Sized + Clone,
+    {
+        use alloc::string::ToString as _;
+
+        use crate::{fen::Fen, zobrist::ZobristHash as _};
+
+        let legal_moves = self.legal_moves();
+        AnalysisNode {
+            hash: self.zobrist_hash(EnPassantMode::Legal),
+            fen: Fen::from_position(self.clone(), EnPassantMode::Legal).to_string(),
+            outcome: self
+                .variant_outcome()
+                .or_else(|| outcome_with_legal_moves(self, &legal_moves)),
+            legal_moves,
+        }
+    }
+
+    /// Plays a move.
+    ///
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PlayError`] if the move is not legal. You can use
+    /// [`Position::play_unchecked()`] if you can guarantee legality.
     fn play(mut self, m: &Move) -> Result<Self, PlayError<Self>>
     where
         Self: Sized,
@@ -627,12 +1762,40 @@ pub trait Position {
             Ok(self)
         } else {
             Err(PlayError {
-                m: m.clone(),
+                m: *m,
                 position: self,
             })
         }
     }
 
+    /// Creates a [`Setup`] snapshot of the position, like
+    /// [`Position::into_setup()`], but without consuming the position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{CastlingMode, Chess, EnPassantMode, Position, Setup};
+    ///
+    /// let pos = Chess::default();
+    /// let setup = pos.to_setup(EnPassantMode::Legal);
+    /// assert_eq!(setup, Setup::initial());
+    /// let pos: Chess = setup.position(CastlingMode::Standard)?;
+    /// # Ok::<_, shakmaty::PositionError<_>>(())
+    /// ```
+    fn to_setup(&self, mode: EnPassantMode) -> Setup /* FINAL */ {
+        Setup {
+            board: self.board().clone(),
+            promoted: self.promoted(),
+            pockets: self.pockets().copied(),
+            turn: self.turn(),
+            castling_rights: self.castles().castling_rights(),
+            ep_square: self.ep_square(mode),
+            remaining_checks: self.remaining_checks().copied(),
+            halfmoves: self.halfmoves(),
+            fullmoves: self.fullmoves(),
+        }
+    }
+
     /// Swap turns and discard en passant rights. This is sometimes called
     /// "playing a null move".
     ///
@@ -669,13 +1832,6 @@ pub struct Chess {
 }
 
 impl Chess {
-    #[cfg(feature = "variant")]
-    fn gives_check(&self, m: &Move) -> bool {
-        let mut pos = self.clone();
-        pos.play_unchecked(m);
-        pos.is_check()
-    }
-
     #[allow(clippy::type_complexity)]
     fn from_setup_unchecked(
         setup: Setup,
@@ -732,6 +1888,92 @@ impl Chess {
             },
         }
     }
+
+    /// Captures the state that playing a move irreversibly overwrites.
+    pub(crate) fn undo_state(&self) -> ChessUndo {
+        ChessUndo {
+            castles: self.castles.clone(),
+            ep_square: self.ep_square,
+            halfmoves: self.halfmoves,
+            fullmoves: self.fullmoves,
+        }
+    }
+
+    /// Takes back the move `m`, which must be the last move played, given
+    /// the state from [`Chess::undo_state()`] before it was played.
+    pub(crate) fn unplay_unchecked(&mut self, m: &Move, undo: ChessUndo) {
+        let color = !self.turn;
+        undo_move(&mut self.board, color, m);
+        self.turn = color;
+        self.castles = undo.castles;
+        self.ep_square = undo.ep_square;
+        self.halfmoves = undo.halfmoves;
+        self.fullmoves = undo.fullmoves;
+    }
+
+    /// Generates pseudo-legal moves, i.e., moves that follow the movement
+    /// rules of the pieces, but may leave the king in check.
+    ///
+    /// Unlike [`Position::legal_moves()`], this does not filter pinned
+    /// pieces, king moves into attacked squares, or moves that fail to
+    /// resolve a check. This is useful for engines that check legality
+    /// lazily. Castling moves are only included if they are fully legal.
+    ///
+    /// This is only available for standard chess, not as part of
+    /// [`Position`]: in variants, moves that leave the king in check are
+    /// not the only illegal ones (e.g., exploding the own king in atomic
+    /// chess), so there is no common notion of pseudo-legal moves.
+    /// Wrappers like [`Zobrist<Chess>`](crate::zobrist::Zobrist) provide
+    /// it via [`Deref`](core::ops::Deref).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, Chess, Position};
+    ///
+    /// let pos: Chess = "4k3/8/8/8/8/8/4r3/4K3 w - - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    ///
+    /// assert_eq!(pos.legal_moves().len(), 3);
+    /// assert_eq!(pos.pseudo_legal_moves().len(), 5);
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    pub fn pseudo_legal_moves(&self) -> MoveList {
+        let mut moves = MoveList::new();
+
+        let king = self
+            .board()
+            .king_of(self.turn())
+            .expect("king in standard chess");
+
+        gen_en_passant(self.board(), self.turn(), self.ep_square, &mut moves);
+
+        let target = !self.us();
+        gen_non_king(self, target, &mut moves);
+        for to in attacks::king_attacks(king) & target {
+            moves.push(Move::Normal {
+                role: Role::King,
+                from: king,
+                capture: self.board().role_at(to),
+                to,
+                promotion: None,
+            });
+        }
+
+        if !self.is_check() {
+            for side in CastlingSide::ALL {
+                gen_castling_moves(self, &self.castles, king, side, &mut moves);
+            }
+        }
+
+        moves
+    }
 }
 
 impl Default for Chess {
@@ -851,6 +2093,9 @@ impl Position for Chess {
             &mut self.fullmoves,
             m,
         );
+
+        #[cfg(feature = "verify")]
+        verify_fen_roundtrip(self);
     }
 
     fn legal_moves(&self) -> MoveList {
@@ -1076,6 +2321,45 @@ pub(crate) mod variant {
                 },
             }
         }
+
+        /// Plays a move without the checks of the `verify` feature, so that
+        /// it can also be used to try pseudo-legal moves.
+        fn play_pseudo_legal(&mut self, m: &Move) {
+            do_move(
+                &mut self.board,
+                &mut Bitboard(0),
+                &mut self.turn,
+                &mut self.castles,
+                &mut self.ep_square,
+                &mut self.halfmoves,
+                &mut self.fullmoves,
+                m,
+            );
+
+            match *m {
+                Move::Normal {
+                    capture: Some(_),
+                    to,
+                    ..
+                }
+                | Move::EnPassant { to, .. } => {
+                    self.board.discard_piece_at(to);
+
+                    let explosion_radius =
+                        attacks::king_attacks(to) & self.board().occupied() & !self.board.pawns();
+
+                    if (explosion_radius & self.board().kings() & self.us()).any() {
+                        self.castles.discard_color(self.turn());
+                    }
+
+                    for explosion in explosion_radius {
+                        self.board.discard_piece_at(explosion);
+                        self.castles.discard_rook(explosion);
+                    }
+                }
+                _ => (),
+            }
+        }
     }
 
     impl Default for Atomic {
@@ -1202,40 +2486,10 @@ pub(crate) mod variant {
         }
 
         fn play_unchecked(&mut self, m: &Move) {
-            do_move(
-                &mut self.board,
-                &mut Bitboard(0),
-                &mut self.turn,
-                &mut self.castles,
-                &mut self.ep_square,
-                &mut self.halfmoves,
-                &mut self.fullmoves,
-                m,
-            );
-
-            match *m {
-                Move::Normal {
-                    capture: Some(_),
-                    to,
-                    ..
-                }
-                | Move::EnPassant { to, .. } => {
-                    self.board.discard_piece_at(to);
+            self.play_pseudo_legal(m);
 
-                    let explosion_radius =
-                        attacks::king_attacks(to) & self.board().occupied() & !self.board.pawns();
-
-                    if (explosion_radius & self.board().kings() & self.us()).any() {
-                        self.castles.discard_color(self.turn());
-                    }
-
-                    for explosion in explosion_radius {
-                        self.board.discard_piece_at(explosion);
-                        self.castles.discard_rook(explosion);
-                    }
-                }
-                _ => (),
-            }
+            #[cfg(feature = "verify")]
+            verify_fen_roundtrip(self);
         }
 
         fn legal_moves(&self) -> MoveList {
@@ -1265,7 +2519,7 @@ pub(crate) mod variant {
             // For simplicity we filter all pseudo legal moves.
             moves.retain(|m| {
                 let mut after = self.clone();
-                after.play_unchecked(m);
+                after.play_pseudo_legal(m);
                 if let Some(our_king) = after.board().king_of(self.turn()) {
                     (after.board.kings() & after.board().by_color(!self.turn())).is_empty()
                         || after
@@ -1512,6 +2766,9 @@ pub(crate) mod variant {
                 &mut self.fullmoves,
                 m,
             );
+
+            #[cfg(feature = "verify")]
+            verify_fen_roundtrip(self);
         }
 
         fn en_passant_moves(&self) -> MoveList {
@@ -1816,6 +3073,9 @@ pub(crate) mod variant {
                 let checks = self.remaining_checks.get_mut(turn);
                 *checks = checks.saturating_sub(1);
             }
+
+            #[cfg(feature = "verify")]
+            verify_fen_roundtrip(self);
         }
 
         fn legal_moves(&self) -> MoveList {
@@ -2070,6 +3330,9 @@ pub(crate) mod variant {
                 &mut self.chess.fullmoves,
                 m,
             );
+
+            #[cfg(feature = "verify")]
+            verify_fen_roundtrip(self);
         }
 
         fn legal_moves(&self) -> MoveList {
@@ -2178,6 +3441,21 @@ pub(crate) mod variant {
                 },
             }
         }
+
+        /// Plays a move without the checks of the `verify` feature, so that
+        /// it can also be used to try pseudo-legal moves.
+        fn play_pseudo_legal(&mut self, m: &Move) {
+            do_move(
+                &mut self.board,
+                &mut Bitboard(0),
+                &mut self.turn,
+                &mut self.castles,
+                &mut None,
+                &mut self.halfmoves,
+                &mut self.fullmoves,
+                m,
+            );
+        }
     }
 
     impl Default for RacingKings {
@@ -2311,16 +3589,10 @@ pub(crate) mod variant {
         }
 
         fn play_unchecked(&mut self, m: &Move) {
-            do_move(
-                &mut self.board,
-                &mut Bitboard(0),
-                &mut self.turn,
-                &mut self.castles,
-                &mut None,
-                &mut self.halfmoves,
-                &mut self.fullmoves,
-                m,
-            );
+            self.play_pseudo_legal(m);
+
+            #[cfg(feature = "verify")]
+            verify_fen_roundtrip(self);
         }
 
         fn legal_moves(&self) -> MoveList {
@@ -2348,7 +3620,7 @@ pub(crate) mod variant {
             // efficiently.
             moves.retain(|m| {
                 let mut after = self.clone();
-                after.play_unchecked(m);
+                after.play_pseudo_legal(m);
                 !after.is_check()
             });
 
@@ -2572,6 +3844,9 @@ pub(crate) mod variant {
                 &mut self.fullmoves,
                 m,
             );
+
+            #[cfg(feature = "verify")]
+            verify_fen_roundtrip(self);
         }
 
         fn legal_moves(&self) -> MoveList {
@@ -2905,6 +4180,31 @@ pub(crate) mod variant {
     }
 }
 
+/// Asserts that the position survives a round trip through FEN, to catch
+/// corrupted state right after the move that caused it.
+#[cfg(feature = "verify")]
+fn verify_fen_roundtrip<P: Position + FromSetup + Clone>(pos: &P) {
+    use alloc::string::ToString as _;
+
+    use crate::fen::Fen;
+
+    let setup = pos.clone().into_setup(EnPassantMode::Always);
+    let fen = Fen::from_setup(setup.clone()).to_string();
+    let parsed = fen
+        .parse::<Fen>()
+        .unwrap_or_else(|err| panic!("unparsable fen {fen}: {err}"));
+    assert_eq!(parsed.as_setup(), &setup, "fen round trip of {fen}");
+
+    let reparsed = P::from_setup(parsed.into_setup(), pos.castles().mode())
+        .or_else(PositionError::ignore_impossible_check)
+        .unwrap_or_else(|err| panic!("invalid position {fen}: {err}"));
+    assert_eq!(
+        reparsed.into_setup(EnPassantMode::Always),
+        setup,
+        "position round trip of {fen}"
+    );
+}
+
 #[allow(clippy::too_many_arguments)] // But typesafe
 fn do_move(
     board: &mut Board,
@@ -2986,6 +4286,105 @@ fn do_move(
     *turn = !color;
 }
 
+/// State of a [`Chess`] position that cannot be recovered from the move
+/// alone.
+#[derive(Clone, Debug)]
+pub(crate) struct ChessUndo {
+    castles: Castles,
+    ep_square: Option<EnPassant>,
+    halfmoves: u32,
+    fullmoves: NonZeroU32,
+}
+
+fn undo_move(board: &mut Board, color: Color, m: &Move) {
+    match *m {
+        Move::Normal {
+            role,
+            from,
+            capture,
+            to,
+            ..
+        } => {
+            board.discard_piece_at(to);
+            if let Some(capture) = capture {
+                board.set_piece_at(to, capture.of(!color));
+            }
+            board.set_piece_at(from, role.of(color));
+        }
+        Move::Castle { king, rook } => {
+            let side = CastlingSide::from_queen_side(rook < king);
+            board.discard_piece_at(Square::from_coords(side.rook_to_file(), rook.rank()));
+            board.discard_piece_at(Square::from_coords(side.king_to_file(), king.rank()));
+            board.set_piece_at(rook, color.rook());
+            board.set_piece_at(king, color.king());
+        }
+        Move::EnPassant { from, to } => {
+            board.discard_piece_at(to);
+            board.set_piece_at(from, color.pawn());
+            board.set_piece_at(Square::from_coords(to.file(), from.rank()), (!color).pawn());
+        }
+        Move::Put { to, .. } => {
+            board.discard_piece_at(to);
+        }
+    }
+}
+
+fn least_valuable_attacker(
+    board: &Board,
+    square: Square,
+    attacker: Color,
+    occupied: Bitboard,
+) -> Option<(Square, Role)> {
+    let attackers = board.attacks_to(square, attacker, occupied) & occupied;
+    Role::ALL.into_iter().find_map(|role| {
+        (attackers & board.by_role(role))
+            .first()
+            .map(|sq| (sq, role))
+    })
+}
+
+/// The captures on a square in static exchange evaluation, as pairs of the
+/// square and role of the capturing piece. Sides alternate, each capturing
+/// with its least valuable attacker, and x-ray attackers are revealed as
+/// pieces leave `occupied`. Shared by [`Position::see_ge()`] and
+/// [`Position::exchange_sequence()`], so that they always agree.
+struct Exchanges<'a> {
+    board: &'a Board,
+    square: Square,
+    side: Color,
+    occupied: Bitboard,
+}
+
+impl<'a> Exchanges<'a> {
+    fn new(board: &'a Board, square: Square, side: Color, occupied: Bitboard) -> Exchanges<'a> {
+        Exchanges {
+            board,
+            square,
+            side,
+            occupied,
+        }
+    }
+}
+
+impl Iterator for Exchanges<'_> {
+    type Item = (Square, Role);
+
+    fn next(&mut self) -> Option<(Square, Role)> {
+        let (from, role) =
+            least_valuable_attacker(self.board, self.square, self.side, self.occupied)?;
+        let occupied = self.occupied ^ from;
+        if role == Role::King
+            && (self.board.attacks_to(self.square, !self.side, occupied) & occupied).any()
+        {
+            // The king can only capture if there are no more defenders.
+            return None;
+        }
+        self.occupied = occupied;
+        self.side = !self.side;
+        Some((from, role))
+    }
+}
+
 fn validate<P: Position>(pos: &P, ep_square: Option<EnPassant>) -> PositionErrorKinds {
     let mut errors = PositionErrorKinds::empty();
 
@@ -3228,6 +4627,26 @@ impl Slider for QueenTag {
     }
 }
 
+/// Outcome of a position without a variant outcome, given its legal moves.
+fn outcome_with_legal_moves<P: Position + ?Sized>(
+    pos: &P,
+    legal_moves: &MoveList,
+) -> Option<Outcome> {
+    if legal_moves.is_empty() {
+        Some(if pos.is_check() {
+            Outcome::Decisive {
+                winner: !pos.turn(),
+            }
+        } else {
+            Outcome::Draw // Stalemate
+        })
+    } else if pos.is_insufficient_material() {
+        Some(Outcome::Draw)
+    } else {
+        None
+    }
+}
+
 fn gen_pawn_moves<P: Position>(pos: &P, target: Bitboard, moves: &mut MoveList) {
     // Safety of unchecked offset calculations: If we shift a set of squares
     // by an offset, then the negated offset is valid for all resulting
@@ -3410,6 +4829,49 @@ mod tests {
             .expect("legal position")
     }
 
+    #[test]
+    fn test_mirrored_moves() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/8/8/2pP4/8/8/8/R3K2R w KQkq c6 0 1",
+            "4k3/1P6/8/8/8/8/6p1/4K2R b K - 0 1",
+        ] {
+            let pos: Chess = setup_fen(fen);
+            let mirrored: Chess = pos
+                .clone()
+                .into_setup(EnPassantMode::Legal)
+                .into_mirrored()
+                .position(CastlingMode::Chess960)
+                .expect("mirrored position is legal");
+
+            let moves = pos.legal_moves();
+            let mut mirrored_moves: MoveList = moves.iter().map(Move::to_mirrored).collect();
+            mirrored_moves.sort_by_key(|m| (m.from(), m.to(), m.promotion()));
+            let mut expected = mirrored.legal_moves();
+            expected.sort_by_key(|m| (m.from(), m.to(), m.promotion()));
+            assert_eq!(mirrored_moves, expected, "{fen}");
+
+            for m in &moves {
+                let after = pos.clone().play(m).expect("legal move");
+                let mirrored_after = mirrored
+                    .clone()
+                    .play(&m.to_mirrored())
+                    .expect("mirrored move is legal");
+                let expected = after.into_setup(EnPassantMode::Legal).into_mirrored();
+                let actual = mirrored_after.into_setup(EnPassantMode::Legal);
+                assert_eq!(actual.board, expected.board, "{fen}: {m}");
+                assert_eq!(actual.turn, expected.turn, "{fen}: {m}");
+                assert_eq!(
+                    actual.castling_rights, expected.castling_rights,
+                    "{fen}: {m}"
+                );
+                assert_eq!(actual.ep_square, expected.ep_square, "{fen}: {m}");
+                assert_eq!(actual.halfmoves, expected.halfmoves, "{fen}: {m}");
+            }
+        }
+    }
+
     #[test]
     fn test_most_known_legals() {
         let pos: Chess = setup_fen("R6R/3Q4/1Q4Q1/4Q3/2Q4Q/Q4Q2/pp1Q4/kBNN1KB1 w - - 0 1");
@@ -3466,17 +4928,1086 @@ mod tests {
     }
 
     #[test]
-    fn test_insufficient_material() {
-        assert_insufficient_material::<Chess>("8/5k2/8/8/8/8/3K4/8 w - - 0 1", true, true);
-        assert_insufficient_material::<Chess>("8/3k4/8/8/2N5/8/3K4/8 b - - 0 1", true, true);
-        assert_insufficient_material::<Chess>("8/4rk2/8/8/8/8/3K4/8 w - - 0 1", true, false);
-        assert_insufficient_material::<Chess>("8/4qk2/8/8/8/8/3K4/8 w - - 0 1", true, false);
-        assert_insufficient_material::<Chess>("8/4bk2/8/8/8/8/3KB3/8 w - - 0 1", false, false);
-        assert_insufficient_material::<Chess>("8/8/3Q4/2bK4/B7/8/1k6/8 w - - 1 68", false, false);
-        assert_insufficient_material::<Chess>("8/5k2/8/8/8/4B3/3K1B2/8 w - - 0 1", true, true);
-        assert_insufficient_material::<Chess>("5K2/8/8/1B6/8/k7/6b1/8 w - - 0 39", true, true);
-        assert_insufficient_material::<Chess>("8/8/8/4k3/5b2/3K4/8/2B5 w - - 0 33", true, true);
-        assert_insufficient_material::<Chess>("3b4/8/8/6b1/8/8/R7/K1k5 w - - 0 1", false, true);
+    fn test_least_valuable_attacker() {
+        let pos: Chess = setup_fen("4k3/8/4r3/3p4/8/5N2/8/3QK3 w - - 0 1");
+        let occupied = pos.board().occupied();
+
+        assert_eq!(
+            pos.least_valuable_attacker(Square::D5, White, occupied),
+            Some((Square::D1, Role::Queen))
+        );
+        assert_eq!(
+            pos.least_valuable_attacker(Square::E5, White, occupied),
+            Some((Square::F3, Role::Knight))
+        );
+        assert_eq!(
+            pos.least_valuable_attacker(Square::E5, Black, occupied),
+            Some((Square::E6, Role::Rook))
+        );
+
+        let pos: Chess = setup_fen("4k3/8/8/3p4/4P3/8/3Q4/3RK3 b - - 0 1");
+        let occupied = pos.board().occupied();
+        assert_eq!(
+            pos.least_valuable_attacker(Square::D5, White, occupied),
+            Some((Square::E4, Role::Pawn))
+        );
+        let occupied = occupied.without(Square::E4);
+        assert_eq!(
+            pos.least_valuable_attacker(Square::D5, White, occupied),
+            Some((Square::D2, Role::Queen))
+        );
+
+        // X-ray attack by the rook behind the queen.
+        let occupied = occupied.without(Square::D2);
+        assert_eq!(
+            pos.least_valuable_attacker(Square::D5, White, occupied),
+            Some((Square::D1, Role::Rook))
+        );
+        let occupied = occupied.without(Square::D1);
+        assert_eq!(
+            pos.least_valuable_attacker(Square::D5, White, occupied),
+            None
+        );
+    }
+
+    #[cfg(feature = "verify")]
+    #[test]
+    fn test_verify_fen_roundtrip() {
+        for (fen, m) in [
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+                Move::Castle {
+                    king: Square::E1,
+                    rook: Square::H1,
+                },
+            ),
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1",
+                Move::Castle {
+                    king: Square::E8,
+                    rook: Square::A8,
+                },
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                Move::EnPassant {
+                    from: Square::E5,
+                    to: Square::F6,
+                },
+            ),
+            (
+                "3r4/4P3/8/8/8/2k5/8/4K3 w - - 0 1",
+                Move::Normal {
+                    role: Role::Pawn,
+                    from: Square::E7,
+                    capture: Some(Role::Rook),
+                    to: Square::D8,
+                    promotion: Some(Role::Queen),
+                },
+            ),
+        ] {
+            let pos: Chess = setup_fen(fen);
+            // Panics if the resulting position does not round trip.
+            pos.play(&m).expect("legal move");
+        }
+    }
+
+    #[test]
+    fn test_mate_pattern() {
+        for (fen, expected) in [
+            ("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", None),
+            (
+                "R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1",
+                Some(MatePattern::BackRank),
+            ),
+            (
+                "3Q2k1/5ppp/8/8/8/8/8/6K1 b - - 0 1",
+                Some(MatePattern::BackRank),
+            ),
+            (
+                "1k1R4/ppp5/8/8/8/8/8/6K1 b - - 0 1",
+                Some(MatePattern::BackRank),
+            ),
+            (
+                "6rk/5Npp/8/8/8/8/8/6K1 b - - 0 1",
+                Some(MatePattern::Smothered),
+            ),
+            // Checkmate, but neither pattern.
+            (
+                "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+                None,
+            ),
+            ("k7/1Q6/2K5/8/8/8/8/8 b - - 0 1", None),
+        ] {
+            let pos: Chess = setup_fen(fen);
+            assert_eq!(pos.mate_pattern(), expected, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_pseudo_legal_moves() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "8/8/8/2k5/3Pp3/8/8/4K2Q b - d3 0 1",
+            "4k3/8/8/8/8/8/4r3/R3K2R w KQ - 0 1",
+        ] {
+            let pos: Chess = setup_fen(fen);
+            let legal = pos.legal_moves();
+            let pseudo_legal = pos.pseudo_legal_moves();
+
+            for m in &legal {
+                assert!(pseudo_legal.contains(m), "{m:?} in {fen}");
+            }
+
+            for m in &pseudo_legal {
+                // Update only the board, because playing moves that leave
+                // the king in check would produce an invalid position.
+                let mut board = pos.board().clone();
+                match *m {
+                    Move::Normal {
+                        role,
+                        from,
+                        to,
+                        promotion,
+                        ..
+                    } => {
+                        board.discard_piece_at(from);
+                        board.set_piece_at(to, promotion.unwrap_or(role).of(pos.turn()));
+                    }
+                    Move::EnPassant { from, to } => {
+                        board.discard_piece_at(from);
+                        board.discard_piece_at(Square::from_coords(to.file(), from.rank()));
+                        board.set_piece_at(to, pos.turn().pawn());
+                    }
+                    Move::Castle { .. } | Move::Put { .. } => {
+                        assert!(legal.contains(m), "{m:?} in {fen}");
+                        continue;
+                    }
+                }
+                let king = board.king_of(pos.turn()).expect("king");
+                let leaves_check = board.attacks_to(king, !pos.turn(), board.occupied()).any();
+                assert_eq!(legal.contains(m), !leaves_check, "{m:?} in {fen}");
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_status_line() {
+        let pos: Chess = setup_fen("4k3/8/8/8/8/8/4r3/R3K2R w KQ - 12 40");
+        assert_eq!(
+            pos.status_line(),
+            "White to move, halfmove 12, fullmove 40, White in check"
+        );
+
+        let pos: Chess = setup_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 3 31");
+        assert_eq!(
+            pos.status_line(),
+            "Black to move, halfmove 3, fullmove 31, Black checkmated, 1-0"
+        );
+
+        let pos: Chess = setup_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 60");
+        assert_eq!(
+            pos.status_line(),
+            "Black to move, halfmove 0, fullmove 60, stalemate, 1/2-1/2"
+        );
+
+        let pos: Chess = setup_fen("4k3/8/8/8/8/8/8/4KB2 w - - 0 70");
+        assert_eq!(
+            pos.status_line(),
+            "White to move, halfmove 0, fullmove 70, 1/2-1/2"
+        );
+    }
+
+    #[test]
+    fn test_see_ge() {
+        fn capture(pos: &Chess, from: Square, to: Square) -> Move {
+            Move::Normal {
+                role: pos.board().role_at(from).expect("piece"),
+                from,
+                capture: pos.board().role_at(to),
+                to,
+                promotion: None,
+            }
+        }
+
+        // Undefended pawn.
+        let pos: Chess = setup_fen("4k3/8/8/3p4/8/8/8/3RK3 w - - 0 1");
+        let m = capture(&pos, Square::D1, Square::D5);
+        assert!(pos.see_ge(&m, 0));
+        assert!(pos.see_ge(&m, 100));
+        assert!(!pos.see_ge(&m, 101));
+
+        // Pawn defended by a pawn.
+        let pos: Chess = setup_fen("4k3/8/2p5/3p4/8/8/8/3RK3 w - - 0 1");
+        let m = capture(&pos, Square::D1, Square::D5);
+        assert!(!pos.see_ge(&m, 0));
+        assert!(pos.see_ge(&m, -400));
+        assert!(!pos.see_ge(&m, -399));
+
+        // Knight takes knight, pawn takes knight, rook takes pawn.
+        let pos: Chess = setup_fen("4k3/8/2p5/3n4/8/4N3/8/3RK3 w - - 0 1");
+        let m = capture(&pos, Square::E3, Square::D5);
+        assert!(pos.see_ge(&m, 100));
+        assert!(!pos.see_ge(&m, 101));
+
+        // Queen takes a pawn defended by the king only.
+        let pos: Chess = setup_fen("8/8/4k3/3p4/8/8/8/3QK3 w - - 0 1");
+        let m = capture(&pos, Square::D1, Square::D5);
+        assert!(!pos.see_ge(&m, 0));
+
+        // The king may only recapture if there are no more attackers.
+        let pos: Chess = setup_fen("8/8/4k3/3p4/8/8/8/3QK2B w - - 0 1");
+        let m = capture(&pos, Square::D1, Square::D5);
+        assert!(pos.see_ge(&m, 0));
+        assert!(pos.see_ge(&m, 100));
+
+        // Quiet moves.
+        let pos: Chess = setup_fen("4k3/8/2p5/8/8/8/8/3RK3 w - - 0 1");
+        let m = capture(&pos, Square::D1, Square::D5);
+        assert!(!pos.see_ge(&m, 0));
+        let m = capture(&pos, Square::D1, Square::D4);
+        assert!(pos.see_ge(&m, 0));
+        assert!(!pos.see_ge(&m, 1));
+    }
+
+    #[test]
+    fn test_material_balance() {
+        let pos = Chess::default();
+        assert_eq!(pos.material_balance(), 0);
+
+        let pos: Chess = setup_fen("rnbqkbnr/ppp1pppp/8/3P4/8/8/PPP1PPPP/RNBQKBNR b KQkq - 0 2");
+        assert_eq!(pos.material_balance(), 100);
+
+        let pos: Chess = setup_fen("4k3/8/8/8/8/8/8/3QK3 b - - 0 1");
+        assert_eq!(pos.material_balance(), 900);
+        let pos: Chess = setup_fen("4k3/rr6/8/8/8/8/8/3QK3 b - - 0 1");
+        assert_eq!(pos.material_balance(), -100);
+    }
+
+    #[test]
+    fn test_king_safety_zone() {
+        let pos: Chess = setup_fen("7k/8/8/8/3K4/8/8/8 w - - 0 1");
+        let zone = pos.king_safety_zone(White);
+        assert_eq!(zone.count(), 9);
+        assert!(zone.contains(Square::D4));
+        assert!(zone.contains(Square::C3));
+        assert!(zone.contains(Square::E5));
+
+        let zone = pos.king_safety_zone(Black);
+        assert_eq!(
+            zone,
+            Bitboard::from_iter([Square::G7, Square::H7, Square::G8, Square::H8])
+        );
+    }
+
+    #[test]
+    fn test_to_setup() {
+        use crate::zobrist::{Zobrist64, ZobristHash as _};
+
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 12 40",
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        ] {
+            let pos: Chess = setup_fen(fen);
+            for mode in [
+                EnPassantMode::Legal,
+                EnPassantMode::PseudoLegal,
+                EnPassantMode::Always,
+            ] {
+                assert_eq!(pos.to_setup(mode), pos.clone().into_setup(mode));
+            }
+
+            let setup = pos.to_setup(EnPassantMode::Legal);
+            let recreated: Chess = setup
+                .position(pos.castles().mode())
+                .expect("legal position");
+            assert_eq!(recreated, pos);
+            assert_eq!(
+                recreated.zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
+                pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal)
+            );
+        }
+    }
+
+    #[test]
+    fn test_mobility() {
+        let pos = Chess::default();
+        assert_eq!(
+            pos.mobility(Color::Black),
+            ByRole {
+                pawn: 16,
+                knight: 4,
+                bishop: 0,
+                rook: 0,
+                queen: 0,
+                king: 0,
+            }
+        );
+
+        // Kiwipete.
+        let pos: Chess =
+            setup_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        assert_eq!(
+            pos.mobility(Color::White),
+            ByRole {
+                pawn: 8,
+                knight: 11,
+                bishop: 11,
+                rook: 5,
+                queen: 9,
+                king: 2,
+            }
+        );
+
+        // Totals match the pseudo-legal move list, except for castling and
+        // en passant.
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ] {
+            let pos: Chess = setup_fen(fen);
+            let expected = pos
+                .pseudo_legal_moves()
+                .iter()
+                .filter(|m| !m.is_castle() && !m.is_en_passant())
+                .count();
+            let total: u32 = pos.mobility(pos.turn()).into_iter().sum();
+            assert_eq!(total as usize, expected, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_is_quiet() {
+        // Tactical: Captures, promotions and checks are available.
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "4k3/1P6/8/8/8/8/8/4K3 w - - 0 1",
+            "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+        ] {
+            let pos: Chess = setup_fen(fen);
+            assert!(!pos.is_quiet(), "{fen}");
+        }
+
+        // Dull endgame with blocked pawns.
+        let pos: Chess = setup_fen("8/8/3k4/1p1p1p2/1P1P1P2/3K4/8/8 w - - 0 1");
+        assert!(pos.is_quiet());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_pretty() {
+        use crate::{uci::UciMove, File};
+
+        let pos: Chess =
+            setup_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1");
+        assert!(!pos.pretty(None).contains('['));
+
+        for uci in ["e5f7", "e1g1", "d5e6"] {
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal uci");
+            let after = pos.clone().play(&m).expect("legal move");
+            let pretty = after.pretty(Some(&m));
+
+            // Find the highlighted squares from the rendered board.
+            let mut highlighted = Bitboard::EMPTY;
+            for (line, rank) in pretty.lines().zip(Rank::ALL.into_iter().rev()) {
+                for (i, ch) in line.chars().enumerate() {
+                    if ch == '[' {
+                        let file = File::new(((i - 2) / 3) as u32);
+                        highlighted.add(Square::from_coords(file, rank));
+                    }
+                }
+            }
+            assert_eq!(
+                highlighted,
+                Bitboard::from(m.to()) | Bitboard::from(m.from().expect("normal move")),
+                "{uci}"
+            );
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_analysis_node() {
+        use alloc::string::ToString as _;
+
+        use crate::{fen::Fen, zobrist::ZobristHash as _};
+
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+            "7k/5Q2/6K1/8/8/8/8/8 b - - 0 1",
+        ] {
+            let pos: Chess = setup_fen(fen);
+            let node = pos.analysis_node();
+            assert_eq!(node.hash, pos.zobrist_hash(EnPassantMode::Legal));
+            assert_eq!(
+                node.fen,
+                Fen::from_position(pos.clone(), EnPassantMode::Legal).to_string()
+            );
+            assert_eq!(node.legal_moves, pos.legal_moves());
+            assert_eq!(node.outcome, pos.outcome());
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_termination() {
+        use crate::zobrist::{RepetitionTable, ZobristHash as _};
+
+        // Checkmate.
+        let pos: Chess = setup_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1");
+        assert_eq!(pos.termination(None), Some(Termination::Checkmate(White)));
+
+        // Stalemate, even with the fifty-move rule.
+        let pos: Chess = setup_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 120 80");
+        assert_eq!(pos.termination(None), Some(Termination::Stalemate));
+
+        let pos: Chess = setup_fen("7k/8/6K1/8/8/8/8/8 b - - 0 1");
+        assert_eq!(
+            pos.termination(None),
+            Some(Termination::InsufficientMaterial)
+        );
+
+        let pos: Chess = setup_fen("7k/8/6K1/8/8/8/8/R7 b - - 100 80");
+        assert_eq!(pos.termination(None), Some(Termination::FiftyMove));
+
+        let pos: Chess = setup_fen("7k/8/6K1/8/8/8/8/R7 b - - 8 80");
+        let mut table = RepetitionTable::new();
+        for _ in 0..2 {
+            table.push(pos.zobrist_hash(EnPassantMode::Legal));
+        }
+        assert_eq!(pos.termination(Some(&table)), None);
+        table.push(pos.zobrist_hash(EnPassantMode::Legal));
+        assert_eq!(
+            pos.termination(Some(&table)),
+            Some(Termination::Repetition(3))
+        );
+    }
+
+    #[test]
+    fn test_is_legal_en_passant() {
+        // Both pawns leave the rank, exposing the king.
+        for fen in [
+            "8/8/8/KPp4r/8/8/8/7k w - c6 0 2",
+            "8/8/8/K1pP3q/8/8/8/7k w - c6 0 2",
+            "7K/8/8/8/1RpP2k1/8/8/8 b - d3 0 1",
+        ] {
+            let pos: Chess = setup_fen(fen);
+            let ep_square = pos.maybe_ep_square().expect("ep square");
+            for from in attacks::pawn_attacks(!pos.turn(), ep_square) & pos.our(Role::Pawn) {
+                assert!(!pos.is_legal_en_passant(from, ep_square), "{fen}");
+            }
+        }
+
+        let pos: Chess = setup_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3");
+        assert!(pos.is_legal_en_passant(Square::E5, Square::F6));
+        assert!(!pos.is_legal_en_passant(Square::E5, Square::D6));
+        assert!(!pos.is_legal_en_passant(Square::D2, Square::F6));
+    }
+
+    #[test]
+    fn test_needs_disambiguation() {
+        use crate::{san::Disambiguation, uci::UciMove};
+
+        for (fen, uci, expected) in [
+            // Two rooks on the same rank.
+            (
+                "4k3/8/8/8/8/8/8/R4RK1 w - - 0 1",
+                "a1d1",
+                Disambiguation::File,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/R4RK1 w - - 0 1",
+                "a1a5",
+                Disambiguation::None,
+            ),
+            // Two rooks on the same file.
+            (
+                "4k3/R7/8/8/8/8/8/R3K3 w - - 0 1",
+                "a1a4",
+                Disambiguation::Rank,
+            ),
+            // Three queens, sharing a file and a rank.
+            (
+                "8/8/1k6/8/4Q2Q/8/8/K6Q w - - 0 1",
+                "h4e1",
+                Disambiguation::Square,
+            ),
+            (
+                "8/8/1k6/8/4Q2Q/8/8/K6Q w - - 0 1",
+                "e4e1",
+                Disambiguation::File,
+            ),
+            // The other knight is pinned.
+            (
+                "4k3/4r3/8/8/8/1N6/4N3/4K3 w - - 0 1",
+                "b3d4",
+                Disambiguation::None,
+            ),
+            // Pawn captures include the file, but are not ambiguous.
+            (
+                "4k3/8/8/3p4/2P1P3/8/8/4K3 w - - 0 1",
+                "c4d5",
+                Disambiguation::None,
+            ),
+        ] {
+            let pos: Chess = setup_fen(fen);
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal uci");
+            assert_eq!(pos.needs_disambiguation(&m), expected, "{uci} in {fen}");
+        }
+    }
+
+    #[test]
+    fn test_attacked_squares() {
+        let pos = Chess::default();
+        assert_eq!(pos.attacked_squares(White), Bitboard(0x00ff_ff7e));
+        assert_eq!(pos.attacked_squares(Black), Bitboard(0x7eff_ff00_0000_0000));
+
+        // Pawns attack diagonally, not forward.
+        let pos: Chess = setup_fen("4k3/8/8/8/4P3/8/8/4K3 w - - 0 1");
+        assert_eq!(
+            pos.attacked_squares(White),
+            Bitboard::from_iter([
+                Square::D5,
+                Square::F5,
+                Square::D1,
+                Square::D2,
+                Square::E2,
+                Square::F2,
+                Square::F1,
+            ])
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_can_claim_draw() {
+        use crate::{
+            uci::UciMove,
+            zobrist::{RepetitionTable, ZobristHash as _},
+        };
+
+        // Fifty-move rule, exactly at the threshold.
+        let table = RepetitionTable::new();
+        let pos: Chess = setup_fen("7k/8/6K1/8/8/8/8/R7 w - - 98 80");
+        assert!(!pos.can_claim_draw(&table));
+        let pos: Chess = setup_fen("7k/8/6K1/8/8/8/8/R7 w - - 100 80");
+        assert!(pos.can_claim_draw(&table));
+
+        // Fifty-move rule, with the next move.
+        let pos: Chess = setup_fen("7k/8/6K1/8/8/8/8/R7 w - - 99 80");
+        assert!(pos.can_claim_draw(&table));
+        let pos: Chess = setup_fen("8/8/8/8/8/8/p1k5/K7 w - - 99 80");
+        assert!(!pos.can_claim_draw(&table));
+
+        // Threefold repetition.
+        let mut pos = Chess::default();
+        let mut table = RepetitionTable::new();
+        table.push(pos.zobrist_hash(EnPassantMode::Legal));
+        let mut claimable = alloc::vec::Vec::new();
+        for uci in [
+            "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+        ] {
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal uci");
+            pos.play_unchecked(&m);
+            table.push(pos.zobrist_hash(EnPassantMode::Legal));
+            claimable.push(pos.can_claim_draw(&table));
+        }
+        assert_eq!(
+            claimable,
+            [false, false, false, false, false, false, true, true]
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_root_moves() {
+        use crate::{san::SanPlus, uci::UciMove};
+
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "8/8/8/2k5/3Pp3/8/8/4K2Q b - d3 0 1",
+        ] {
+            let pos: Chess = setup_fen(fen);
+            let root_moves = pos.root_moves();
+            assert_eq!(root_moves.len(), pos.legal_moves().len());
+
+            for (m, san, uci) in root_moves {
+                let san: SanPlus = san.parse().expect("valid san");
+                assert_eq!(san.san.to_move(&pos), Ok(m), "{san} in {fen}");
+                assert_eq!(san, SanPlus::from_move(pos.clone(), &m));
+
+                let uci: UciMove = uci.parse().expect("valid uci");
+                assert_eq!(uci.to_move(&pos).ok(), Some(m), "{uci} in {fen}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_game_phase() {
+        for (fen, phase) in [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                256,
+            ),
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", 0),
+            ("4k3/pppppppp/8/8/8/8/PPPPPPPP/4K3 w - - 0 1", 0),
+            ("r3k3/8/8/8/8/8/8/R3K3 w - - 0 1", 42),
+            ("k7/pp6/8/8/8/8/QQQQQQQ1/K7 w - - 0 1", 256),
+        ] {
+            let pos: Chess = setup_fen(fen);
+            assert_eq!(pos.game_phase(), phase, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_legal_moves_excluding() {
+        let pos = Chess::default();
+        let excluded = [
+            Move::Normal {
+                role: Role::Pawn,
+                from: Square::E2,
+                capture: None,
+                to: Square::E4,
+                promotion: None,
+            },
+            Move::Normal {
+                role: Role::Knight,
+                from: Square::G1,
+                capture: None,
+                to: Square::F3,
+                promotion: None,
+            },
+        ];
+
+        let moves = pos.legal_moves_excluding(&excluded);
+        assert_eq!(moves.len(), 20 - 2);
+        assert!(moves.iter().all(|m| !excluded.contains(m)));
+        assert!(moves.iter().all(|m| pos.is_legal(m)));
+
+        assert_eq!(pos.legal_moves_excluding(&[]), pos.legal_moves());
+    }
+
+    #[test]
+    fn test_can_castle() {
+        for (fen, king_side, queen_side) in [
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", true, true),
+            // King in check.
+            ("r3k2r/8/8/8/8/8/4q3/R3K2R w KQkq - 0 1", false, false),
+            // Passing through check.
+            ("r3kr2/8/8/8/8/8/8/R3K2R w KQq - 0 1", false, true),
+            // Ending up in check.
+            ("r3k2r/8/8/2b5/8/8/8/R3K2R w KQkq - 0 1", false, true),
+            // Path blocked.
+            ("r3k2r/8/8/8/8/8/8/RN2K1NR w KQkq - 0 1", false, false),
+            // No rights.
+            ("r3k2r/8/8/8/8/8/8/R3K2R w kq - 0 1", false, false),
+        ] {
+            let pos: Chess = setup_fen(fen);
+            assert_eq!(
+                pos.can_castle(White, CastlingSide::KingSide),
+                king_side,
+                "{fen}"
+            );
+            assert_eq!(
+                pos.can_castle(White, CastlingSide::QueenSide),
+                queen_side,
+                "{fen}"
+            );
+            assert!(!pos.can_castle(Black, CastlingSide::KingSide), "{fen}");
+        }
+
+        // The right exists, but castling is not legal.
+        let pos: Chess = setup_fen("r3k2r/8/8/8/8/8/4q3/R3K2R w KQkq - 0 1");
+        assert!(pos.castles().has(White, CastlingSide::KingSide));
+        assert!(!pos.can_castle(White, CastlingSide::KingSide));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_exchange_sequence() {
+        // Knight takes knight, pawn recaptures, but the rook should not
+        // take the pawn, defended by the other rook.
+        let pos: Chess = setup_fen("3rk3/8/2p5/3n4/8/4N3/8/3RK3 w - - 0 1");
+        assert_eq!(
+            pos.exchange_sequence(Square::D5),
+            [(Square::E3, Role::Knight), (Square::C6, Role::Pawn)]
+        );
+
+        // Attackers are used in order of value, including x-rays.
+        let pos: Chess = setup_fen("3rk3/8/4p3/3p4/2P5/8/3R4/3RK3 w - - 0 1");
+        assert_eq!(
+            pos.exchange_sequence(Square::D5),
+            [
+                (Square::C4, Role::Pawn),
+                (Square::E6, Role::Pawn),
+                (Square::D2, Role::Rook),
+                (Square::D8, Role::Rook),
+                (Square::D1, Role::Rook),
+            ]
+        );
+
+        // The rook should not take a pawn defended by a pawn.
+        let pos: Chess = setup_fen("4k3/8/2p5/3p4/8/8/8/3RK3 w - - 0 1");
+        assert_eq!(pos.exchange_sequence(Square::D5), []);
+
+        // The king may only capture undefended pieces.
+        let pos: Chess = setup_fen("8/8/4k3/3q4/8/8/8/3QK3 w - - 0 1");
+        assert_eq!(
+            pos.exchange_sequence(Square::D5),
+            [(Square::D1, Role::Queen), (Square::E6, Role::King)]
+        );
+        let pos: Chess = setup_fen("8/8/4k3/3q4/8/8/8/3QK2B w - - 0 1");
+        assert_eq!(
+            pos.exchange_sequence(Square::D5),
+            [(Square::H1, Role::Bishop)]
+        );
+        // Agrees with see_ge() for the capture by the least valuable
+        // attacker.
+        for fen in [
+            "3rk3/8/2p5/3n4/8/4N3/8/3RK3 w - - 0 1",
+            "3rk3/8/4p3/3p4/2P5/8/3R4/3RK3 w - - 0 1",
+            "4k3/8/2p5/3p4/8/8/8/3RK3 w - - 0 1",
+            "8/8/4k3/3q4/8/8/8/3QK3 w - - 0 1",
+            "8/8/4k3/3q4/8/8/8/3QK2B w - - 0 1",
+        ] {
+            let pos: Chess = setup_fen(fen);
+            let (from, role) = pos
+                .least_valuable_attacker(Square::D5, pos.turn(), pos.board().occupied())
+                .expect("attacker");
+            let m = Move::Normal {
+                role,
+                from,
+                capture: pos.board().role_at(Square::D5),
+                to: Square::D5,
+                promotion: None,
+            };
+            assert_eq!(
+                pos.see_ge(&m, 0),
+                !pos.exchange_sequence(Square::D5).is_empty(),
+                "{fen}"
+            );
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_material_signature() {
+        for (fen, signature) in [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "KQRRBBNNPPPPPPPPvKQRRBBNNPPPPPPPP",
+            ),
+            ("4k3/8/8/8/8/8/8/4K3 w - - 0 1", "KvK"),
+            ("4k3/8/8/8/8/8/8/3QK3 w - - 0 1", "KQvK"),
+            ("4k3/8/8/8/8/8/8/3RK2B w - - 0 1", "KRBvK"),
+            ("3rk3/8/8/8/8/8/8/2B1K3 w - - 0 1", "KRvKB"),
+            ("4k3/4p3/8/8/8/8/8/2N1K3 w - - 0 1", "KNvKP"),
+            ("2q1k3/8/8/8/8/8/2PP4/2R1K3 w - - 0 1", "KRPPvKQ"),
+        ] {
+            let pos: Chess = setup_fen(fen);
+            assert_eq!(pos.material_signature(), signature, "{fen}");
+
+            let mirrored: Chess = pos
+                .into_setup(EnPassantMode::Legal)
+                .into_mirrored()
+                .position(CastlingMode::Chess960)
+                .expect("mirrored position is legal");
+            assert_eq!(mirrored.material_signature(), signature, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_terminal_value() {
+        let mated: Chess = setup_fen("7k/6Q1/6K1/8/8/8/8/8 b - - 0 1");
+        assert_eq!(mated.terminal_value(0), Some(-MATE_SCORE));
+        assert_eq!(mated.terminal_value(7), Some(-MATE_SCORE + 7));
+        assert!(mated.terminal_value(7) > mated.terminal_value(3));
+
+        let stalemate: Chess = setup_fen("7k/8/6QK/8/8/8/8/8 b - - 0 1");
+        assert_eq!(stalemate.terminal_value(5), Some(0));
+
+        let insufficient: Chess = setup_fen("7k/8/6NK/8/8/8/8/8 b - - 0 1");
+        assert_eq!(insufficient.terminal_value(5), Some(0));
+
+        let ongoing: Chess = setup_fen("7k/8/6QK/8/8/8/8/8 w - - 0 1");
+        assert_eq!(ongoing.terminal_value(5), None);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_sort_key_order() {
+        use alloc::string::ToString as _;
+
+        let pos: Chess = "2q1k3/1P6/8/3q4/4P3/8/8/4K3 w - - 0 1"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+        let mut moves = pos.legal_moves();
+        moves.sort_by_key(|m| -m.sort_key());
+        let ucis: alloc::vec::Vec<_> = moves
+            .iter()
+            .take_while(|m| m.sort_key() > 0)
+            .map(|m| m.to_uci(CastlingMode::Standard).to_string())
+            .collect();
+        assert_eq!(
+            ucis,
+            ["b7c8q", "b7c8r", "b7c8b", "b7c8n", "b7b8q", "e4d5", "b7b8r", "b7b8b", "b7b8n",]
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_legal_moves_order() {
+        use alloc::string::ToString as _;
+
+        let moves: MoveList = Chess::default().legal_moves();
+        let ucis: alloc::vec::Vec<_> = moves
+            .iter()
+            .map(|m| m.to_uci(CastlingMode::Standard).to_string())
+            .collect();
+        assert_eq!(
+            ucis,
+            [
+                "a2a3", "b2b3", "c2c3", "d2d3", "e2e3", "f2f3", "g2g3", "h2h3", "a2a4", "b2b4",
+                "c2c4", "d2d4", "e2e4", "f2f4", "g2g4", "h2h4", "b1a3", "b1c3", "g1f3", "g1h3",
+            ]
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_legal_moves_order_in_check() {
+        use alloc::string::ToString as _;
+
+        // Evasions: king moves first, then the pawn capture, then knight
+        // blocks and captures.
+        let pos: Chess = "4k3/8/8/8/1b6/P7/2N5/RN2K3 w Q - 0 1"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+        let ucis: alloc::vec::Vec<_> = pos
+            .legal_moves()
+            .iter()
+            .map(|m| m.to_uci(CastlingMode::Standard).to_string())
+            .collect();
+        assert_eq!(
+            ucis,
+            ["e1d1", "e1f1", "e1e2", "e1f2", "a3b4", "b1d2", "b1c3", "c2b4"]
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_legal_moves_order_en_passant() {
+        use alloc::string::ToString as _;
+
+        // En passant first, then pawn pushes, rook moves, king moves, and
+        // castling.
+        let pos: Chess = "4k3/8/8/3pP3/8/8/8/4K2R w K d6 0 2"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+        let ucis: alloc::vec::Vec<_> = pos
+            .legal_moves()
+            .iter()
+            .map(|m| m.to_uci(CastlingMode::Standard).to_string())
+            .collect();
+        assert_eq!(
+            ucis,
+            [
+                "e5d6", "e5e6", "h1f1", "h1g1", "h1h2", "h1h3", "h1h4", "h1h5", "h1h6", "h1h7",
+                "h1h8", "e1d1", "e1f1", "e1d2", "e1e2", "e1f2", "e1g1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_setup() {
+        use crate::fen::Fen;
+
+        for (fen, errors) in [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                PositionErrorKinds::empty(),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/8 w - - 0 1",
+                PositionErrorKinds::MISSING_KING,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/2K1K3 w - - 0 1",
+                PositionErrorKinds::TOO_MANY_KINGS,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/P3K3 w - - 0 1",
+                PositionErrorKinds::PAWNS_ON_BACKRANK,
+            ),
+            (
+                "3Pk3/8/8/8/8/8/8/4K3 b - - 0 1",
+                PositionErrorKinds::PAWNS_ON_BACKRANK,
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4K2R b - - 0 1",
+                PositionErrorKinds::empty(),
+            ),
+            (
+                "4k3/8/8/8/8/8/8/4R1K1 w - - 0 1",
+                PositionErrorKinds::OPPOSITE_CHECK,
+            ),
+        ] {
+            let setup = fen.parse::<Fen>().expect("valid fen").into_setup();
+            let result = validate_setup(&setup, CastlingMode::Standard);
+            if errors.is_empty() {
+                assert_eq!(result, Ok(()), "{fen}");
+            } else {
+                assert_eq!(result, Err(errors), "{fen}");
+            }
+            assert_eq!(
+                result.err().unwrap_or(PositionErrorKinds::empty()),
+                Chess::from_setup(setup, CastlingMode::Standard)
+                    .map_or_else(|err| err.kinds(), |_| PositionErrorKinds::empty())
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_hanging() {
+        // The knight on e5 is attacked by the rook and defended by nothing.
+        let pos: Chess = setup_fen("4k3/8/4r3/4N3/8/8/8/4K3 w - - 0 1");
+        assert!(pos.is_hanging(Square::E5));
+        assert!(!pos.is_hanging(Square::E6));
+        assert!(!pos.is_hanging(Square::D4));
+
+        // Defended once by the pawn and attacked once by the rook.
+        let pos: Chess = setup_fen("4k3/8/4r3/4N3/3P4/8/8/4K3 w - - 0 1");
+        assert!(!pos.is_hanging(Square::E5));
+
+        // X-ray attackers are not counted.
+        let pos: Chess = setup_fen("4k3/4r3/4r3/4N3/3P4/8/8/4K3 w - - 0 1");
+        assert!(!pos.is_hanging(Square::E5));
+
+        // Attacked twice and defended once.
+        let pos: Chess = setup_fen("4k3/8/3br3/4N3/3P4/8/8/4K3 w - - 0 1");
+        assert!(pos.is_hanging(Square::E5));
+    }
+
+    #[test]
+    fn test_from_board() {
+        let board: Board = "8/8/3k4/8/8/8/4K3/8".parse().expect("valid board fen");
+
+        let pos = Chess::from_board(board.clone(), Black, CastlingMode::Standard)
+            .expect("legal position");
+        assert_eq!(pos.turn(), Black);
+        assert!(pos.castles().is_empty());
+        assert_eq!(pos.maybe_ep_square(), None);
+        assert!(pos.is_insufficient_material());
+        assert_eq!(pos.legal_moves().len(), 8);
+
+        let board: Board = "8/8/3k4/8/8/8/3RK3/8".parse().expect("valid board fen");
+        let err =
+            Chess::from_board(board, White, CastlingMode::Standard).expect_err("opposite check");
+        assert_eq!(err.kinds(), PositionErrorKinds::OPPOSITE_CHECK);
+    }
+
+    #[test]
+    fn test_insufficient_material() {
+        assert_insufficient_material::<Chess>("8/5k2/8/8/8/8/3K4/8 w - - 0 1", true, true);
+        assert_insufficient_material::<Chess>("8/3k4/8/8/2N5/8/3K4/8 b - - 0 1", true, true);
+        assert_insufficient_material::<Chess>("8/4rk2/8/8/8/8/3K4/8 w - - 0 1", true, false);
+        assert_insufficient_material::<Chess>("8/4qk2/8/8/8/8/3K4/8 w - - 0 1", true, false);
+        assert_insufficient_material::<Chess>("8/4bk2/8/8/8/8/3KB3/8 w - - 0 1", false, false);
+        assert_insufficient_material::<Chess>("8/8/3Q4/2bK4/B7/8/1k6/8 w - - 1 68", false, false);
+        assert_insufficient_material::<Chess>("8/5k2/8/8/8/4B3/3K1B2/8 w - - 0 1", true, true);
+        assert_insufficient_material::<Chess>("5K2/8/8/1B6/8/k7/6b1/8 w - - 0 39", true, true);
+        assert_insufficient_material::<Chess>("8/8/8/4k3/5b2/3K4/8/2B5 w - - 0 33", true, true);
+        assert_insufficient_material::<Chess>("3b4/8/8/6b1/8/8/R7/K1k5 w - - 0 1", false, true);
+    }
+
+    #[test]
+    fn test_is_insufficient_material() {
+        for (fen, insufficient) in [
+            ("8/5k2/8/8/8/8/3K4/8 w - - 0 1", true),
+            ("8/5k2/8/8/8/8/3KB3/8 w - - 0 1", true),
+            // Helpmate is possible, so this is not a draw by rule.
+            ("8/5k2/5n2/8/8/8/3KB3/8 w - - 0 1", false),
+            ("8/5k2/5p2/8/8/8/3KN3/8 w - - 0 1", false),
+        ] {
+            let pos: Chess = setup_fen(fen);
+            assert_eq!(pos.is_insufficient_material(), insufficient, "{fen}");
+            assert_eq!(
+                pos.outcome(),
+                insufficient.then_some(Outcome::Draw),
+                "{fen}"
+            );
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_is_draw() {
+        use crate::zobrist::{RepetitionTable, ZobristHash as _};
+
+        // Stalemate.
+        let pos: Chess = setup_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 60");
+        assert!(pos.is_draw(None));
+
+        // Insufficient material.
+        let pos: Chess = setup_fen("4k3/8/8/8/8/8/8/4KB2 w - - 0 70");
+        assert!(pos.is_draw(None));
+
+        // 75-move rule, unless the last move delivered checkmate.
+        let pos: Chess = setup_fen("4k3/8/8/8/8/8/8/R3K3 w - - 149 120");
+        assert!(!pos.is_draw(None));
+        let pos: Chess = setup_fen("4k3/8/8/8/8/8/8/R3K3 w - - 150 120");
+        assert!(pos.is_draw(None));
+        let pos: Chess = setup_fen("R3k3/8/4K3/8/8/8/8/8 b - - 150 120");
+        assert!(pos.is_checkmate());
+        assert!(!pos.is_draw(None));
+
+        // Fivefold repetition.
+        let mut pos = Chess::default();
+        let mut table = RepetitionTable::new();
+        table.push(pos.zobrist_hash(EnPassantMode::Legal));
+        for _ in 0..4 {
+            assert!(!pos.is_draw(Some(&table)));
+            for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+                let m = uci
+                    .parse::<crate::uci::UciMove>()
+                    .expect("valid uci")
+                    .to_move(&pos)
+                    .expect("legal uci");
+                pos.play_unchecked(&m);
+                table.push(pos.zobrist_hash(EnPassantMode::Legal));
+            }
+        }
+        assert!(pos.is_draw(Some(&table)));
+        assert!(!pos.is_draw(None));
     }
 
     #[test]
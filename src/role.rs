@@ -1,4 +1,9 @@
-use core::{array, convert::identity, num};
+use core::{
+    array,
+    convert::identity,
+    num,
+    ops::{Add, AddAssign, Index, IndexMut, Sub, SubAssign},
+};
 
 use crate::{color::Color, types::Piece, util::out_of_range_error};
 
@@ -103,6 +108,48 @@ impl Role {
         }
     }
 
+    /// Gets a conventional material value of the piece type in
+    /// centipawns: 100, 300, 300, 500 and 900 for pawns, knights, bishops,
+    /// rooks and queens. The king has no material value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::Role;
+    ///
+    /// assert_eq!(Role::Rook.value(), 500);
+    /// ```
+    pub const fn value(self) -> i32 {
+        match self {
+            Role::Pawn => 100,
+            Role::Knight | Role::Bishop => 300,
+            Role::Rook => 500,
+            Role::Queen => 900,
+            Role::King => 0,
+        }
+    }
+
+    /// Gets the Unicode figurine for the piece type, as used in figurine
+    /// algebraic notation. Uses the white symbols for either color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::Role;
+    ///
+    /// assert_eq!(Role::Knight.figurine(), '♘');
+    /// ```
+    pub const fn figurine(self) -> char {
+        match self {
+            Role::Pawn => '♙',
+            Role::Knight => '♘',
+            Role::Bishop => '♗',
+            Role::Rook => '♖',
+            Role::Queen => '♕',
+            Role::King => '♔',
+        }
+    }
+
     /// `Pawn`, `Knight`, `Bishop`, `Rook`, `Queen`, and `King`, in this order.
     pub const ALL: [Role; 6] = [
         Role::Pawn,
@@ -323,6 +370,70 @@ impl<T: Clone> ByRole<&T> {
     }
 }
 
+impl<T> Index<Role> for ByRole<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, role: Role) -> &T {
+        self.get(role)
+    }
+}
+
+impl<T> IndexMut<Role> for ByRole<T> {
+    #[inline]
+    fn index_mut(&mut self, role: Role) -> &mut T {
+        self.get_mut(role)
+    }
+}
+
+/// Adds the values for each role, for example to combine material counts.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Board, ByRole, Color, Role};
+///
+/// let mut material = Board::new().material_side(Color::White);
+/// material[Role::Queen] += 1;
+///
+/// let material = material.map(i32::from) - Board::new().material_side(Color::Black).map(i32::from);
+/// assert_eq!(material[Role::Queen], 1);
+/// assert_eq!(material[Role::Pawn], 0);
+/// ```
+impl<T: Add<Output = T>> Add for ByRole<T> {
+    type Output = ByRole<T>;
+
+    fn add(self, other: ByRole<T>) -> ByRole<T> {
+        self.zip(other).map(|(a, b)| a + b)
+    }
+}
+
+impl<T: AddAssign> AddAssign for ByRole<T> {
+    fn add_assign(&mut self, other: ByRole<T>) {
+        for (a, b) in self.iter_mut().zip(other) {
+            *a += b;
+        }
+    }
+}
+
+/// Subtracts the values for each role, for example to compute material
+/// differences.
+impl<T: Sub<Output = T>> Sub for ByRole<T> {
+    type Output = ByRole<T>;
+
+    fn sub(self, other: ByRole<T>) -> ByRole<T> {
+        self.zip(other).map(|(a, b)| a - b)
+    }
+}
+
+impl<T: SubAssign> SubAssign for ByRole<T> {
+    fn sub_assign(&mut self, other: ByRole<T>) {
+        for (a, b) in self.iter_mut().zip(other) {
+            *a -= b;
+        }
+    }
+}
+
 impl<T> IntoIterator for ByRole<T> {
     type Item = T;
     type IntoIter = array::IntoIter<T, 6>;
@@ -339,3 +450,34 @@ impl<T> IntoIterator for ByRole<T> {
         .into_iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_by_role_arithmetic() {
+        let white = ByRole {
+            pawn: 8,
+            knight: 2,
+            bishop: 2,
+            rook: 2,
+            queen: 1,
+            king: 1,
+        };
+        let mut black = white;
+
+        // Capture a black knight.
+        black[Role::Knight] -= 1;
+        let mut captured = ByRole::default();
+        captured[Role::Knight] = 1;
+        assert_eq!(black + captured, white);
+
+        black += captured;
+        assert_eq!(white - black, ByRole::default());
+
+        black -= white;
+        assert_eq!(black, ByRole::default());
+        assert_eq!(white[Role::Pawn], 8);
+    }
+}
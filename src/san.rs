@@ -52,7 +52,15 @@
 //! # Ok::<_, CommonError>(())
 //! ```
 
-use core::{fmt, str::FromStr};
+use core::{
+    fmt::{self, Write as _},
+    str::FromStr,
+};
+#[cfg(feature = "std")]
+use std::{
+    collections::HashMap,
+    string::{String, ToString as _},
+};
 
 use crate::{
     util::AppendAscii, CastlingSide, File, Move, MoveList, Outcome, Position, Rank, Role, Square,
@@ -269,7 +277,7 @@ impl San {
                     .split_first()
                     .map_or(Err(SanError::IllegalSan), |(m, others)| {
                         if others.is_empty() {
-                            Ok(m.clone())
+                            Ok(*m)
                         } else {
                             Err(SanError::AmbiguousSan)
                         }
@@ -316,34 +324,16 @@ impl San {
                 to,
                 promotion,
             } => {
-                // Disambiguate.
-                let mut ambiguous = false;
-                let mut ambiguous_file = false;
-                let mut ambiguous_rank = false;
-                for candidate in moves {
-                    match *candidate {
-                        Move::Normal {
-                            role: r,
-                            to: t,
-                            promotion: p,
-                            from: f,
-                            ..
-                        } if from != f && role == r && to == t && promotion == p => {
-                            ambiguous = true;
-                            if from.rank() == f.rank() {
-                                ambiguous_rank = true;
-                            }
-                            if from.file() == f.file() {
-                                ambiguous_file = true;
-                            }
-                        }
-                        _ => {}
-                    }
-                }
+                let (file, rank) = match disambiguation(role, from, to, promotion, moves) {
+                    Disambiguation::None => (None, None),
+                    Disambiguation::File => (Some(from.file()), None),
+                    Disambiguation::Rank => (None, Some(from.rank())),
+                    Disambiguation::Square => (Some(from.file()), Some(from.rank())),
+                };
                 San::Normal {
                     role,
-                    file: (ambiguous && (!ambiguous_file || ambiguous_rank)).then(|| from.file()),
-                    rank: ambiguous_file.then(|| from.rank()),
+                    file,
+                    rank,
                     capture: capture.is_some(),
                     to,
                     promotion,
@@ -449,7 +439,7 @@ impl San {
                 }
                 _ => false,
             },
-            San::Castle(side) => m.castling_side().map_or(false, |s| side == s),
+            San::Castle(side) => m.castling_side() == Some(side),
             San::Put { role, to } => match *m {
                 Move::Put { role: r, to: t } => r == role && to == t,
                 _ => false,
@@ -459,6 +449,18 @@ impl San {
     }
 
     fn append_to<W: AppendAscii>(&self, f: &mut W) -> Result<(), W::Error> {
+        self.append_with_roles_to(f, None, |f, role| f.append_ascii(role.upper_char()))
+    }
+
+    /// Renders the move followed by the optional `suffix`, using
+    /// `append_role` to write piece symbols. This is shared by SAN, with
+    /// ASCII letters, and FAN, with figurines.
+    fn append_with_roles_to<W: AppendAscii>(
+        &self,
+        f: &mut W,
+        suffix: Option<Suffix>,
+        mut append_role: impl FnMut(&mut W, Role) -> Result<(), W::Error>,
+    ) -> Result<(), W::Error> {
         match *self {
             San::Normal {
                 role,
@@ -469,7 +471,7 @@ impl San {
                 promotion,
             } => {
                 if role != Role::Pawn {
-                    f.append_ascii(role.upper_char())?;
+                    append_role(f, role)?;
                 }
                 if let Some(file) = file {
                     f.append_ascii(file.char())?;
@@ -483,7 +485,7 @@ impl San {
                 to.append_to(f)?;
                 if let Some(promotion) = promotion {
                     f.append_ascii('=')?;
-                    f.append_ascii(promotion.upper_char())?;
+                    append_role(f, promotion)?;
                 }
             }
             San::Castle(CastlingSide::KingSide) => {
@@ -500,7 +502,7 @@ impl San {
             }
             San::Put { role, to } => {
                 if role != Role::Pawn {
-                    f.append_ascii(role.upper_char())?;
+                    append_role(f, role)?;
                 }
                 f.append_ascii('@')?;
                 to.append_to(f)?;
@@ -510,9 +512,30 @@ impl San {
                 f.append_ascii('-')?;
             }
         }
+        if let Some(suffix) = suffix {
+            f.append_ascii(suffix.char())?;
+        }
         Ok(())
     }
 
+    /// Displays the move in figurine algebraic notation, like `♘f3`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::san::San;
+    ///
+    /// let san: San = "Nf3".parse()?;
+    /// assert_eq!(san.fan().to_string(), "♘f3");
+    /// # Ok::<_, shakmaty::san::ParseSanError>(())
+    /// ```
+    pub const fn fan(&self) -> Fan<'_> {
+        Fan {
+            san: self,
+            suffix: None,
+        }
+    }
+
     #[cfg(feature = "alloc")]
     pub fn append_to_string(&self, s: &mut alloc::string::String) {
         let _ = self.append_to(s);
@@ -543,6 +566,63 @@ impl fmt::Display for San {
     }
 }
 
+/// Information required to unambiguously identify the origin square of a
+/// piece move in SAN, as determined by
+/// [`Position::needs_disambiguation()`](crate::Position::needs_disambiguation()).
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+pub enum Disambiguation {
+    /// The move is unambiguous, like `Nf3`.
+    None,
+    /// The file of the origin square is required, like `Rad1`.
+    File,
+    /// The rank of the origin square is required, like `R1d2`.
+    Rank,
+    /// Both file and rank of the origin square are required, like `Qh4e1`.
+    Square,
+}
+
+/// Determines which parts of `from` tell the piece move from `from` to `to`
+/// apart from other moves of the same `role` and `promotion` to `to` in
+/// `moves`.
+pub(crate) fn disambiguation(
+    role: Role,
+    from: Square,
+    to: Square,
+    promotion: Option<Role>,
+    moves: &[Move],
+) -> Disambiguation {
+    let mut ambiguous = false;
+    let mut ambiguous_file = false;
+    let mut ambiguous_rank = false;
+    for candidate in moves {
+        match *candidate {
+            Move::Normal {
+                role: r,
+                to: t,
+                promotion: p,
+                from: f,
+                ..
+            } if from != f && role == r && to == t && promotion == p => {
+                ambiguous = true;
+                if from.rank() == f.rank() {
+                    ambiguous_rank = true;
+                }
+                if from.file() == f.file() {
+                    ambiguous_file = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    match (ambiguous, ambiguous_file, ambiguous_rank) {
+        (false, _, _) => Disambiguation::None,
+        (true, false, _) => Disambiguation::File,
+        (true, true, false) => Disambiguation::Rank,
+        (true, true, true) => Disambiguation::Square,
+    }
+}
+
 /// Check (`+`) or checkmate (`#`) suffix.
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
 pub enum Suffix {
@@ -644,12 +724,17 @@ impl SanPlus {
         }
     }
 
-    fn append_to<W: AppendAscii>(&self, f: &mut W) -> Result<(), W::Error> {
-        self.san.append_to(f)?;
-        if let Some(suffix) = self.suffix {
-            f.append_ascii(suffix.char())?;
+    /// Displays the move in figurine algebraic notation, like `♘f3+`.
+    pub const fn fan(&self) -> Fan<'_> {
+        Fan {
+            san: &self.san,
+            suffix: self.suffix,
         }
-        Ok(())
+    }
+
+    fn append_to<W: AppendAscii>(&self, f: &mut W) -> Result<(), W::Error> {
+        self.san
+            .append_with_roles_to(f, self.suffix, |f, role| f.append_ascii(role.upper_char()))
     }
 
     #[cfg(feature = "alloc")]
@@ -682,6 +767,48 @@ impl fmt::Display for SanPlus {
     }
 }
 
+/// Maps the SAN of each legal move to the move, to look up many moves
+/// without converting each one individually.
+///
+/// Keys do not include check or checkmate suffixes.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, Position, san::san_move_map};
+///
+/// let pos = Chess::default();
+/// let moves = san_move_map(&pos);
+/// assert_eq!(moves.len(), 20);
+/// assert_eq!(moves["Nf3"].to_string(), "Ng1-f3");
+/// ```
+#[cfg(feature = "std")]
+pub fn san_move_map<P: Position>(pos: &P) -> HashMap<String, Move> {
+    let moves = pos.legal_moves();
+    moves
+        .iter()
+        .map(|m| (San::disambiguate(m, &moves).to_string(), *m))
+        .collect()
+}
+
+/// Displays a [`San`] or [`SanPlus`] in figurine algebraic notation (FAN),
+/// using Unicode symbols instead of English piece letters.
+///
+/// Created by [`San::fan()`] or [`SanPlus::fan()`].
+#[derive(Debug, Clone)]
+pub struct Fan<'a> {
+    san: &'a San,
+    suffix: Option<Suffix>,
+}
+
+impl fmt::Display for Fan<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Figurines are not ASCII, so they bypass AppendAscii.
+        self.san
+            .append_with_roles_to(f, self.suffix, |f, role| f.write_char(role.figurine()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[cfg(feature = "alloc")]
@@ -710,6 +837,137 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_san_move_map() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "N1N5/8/8/8/8/k7/8/N1N2K2 w - - 0 1",
+        ] {
+            let pos: Chess = fen
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+            let map = san_move_map(&pos);
+            assert_eq!(map.len(), pos.legal_moves().len(), "{fen}");
+            for (san, m) in &map {
+                let parsed: San = san.parse().expect("valid san");
+                assert_eq!(parsed.to_move(&pos), Ok(*m), "{san} in {fen}");
+            }
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_fan() {
+        let pos: Chess = "3r2k1/4P3/8/8/8/8/8/R3K1N1 w - - 0 1"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+
+        for (uci, expected) in [
+            ("g1f3", "♘f3"),
+            ("e7e8q", "e8=♕+"),
+            ("e7d8n", "exd8=♘"),
+            ("a1a7", "♖a7"),
+        ] {
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal uci");
+            assert_eq!(
+                SanPlus::from_move(pos.clone(), &m).fan().to_string(),
+                expected
+            );
+        }
+
+        let san: San = "e4".parse().expect("valid san");
+        assert_eq!(san.fan().to_string(), "e4");
+        let san: San = "Q@h7".parse().expect("valid san");
+        assert_eq!(san.fan().to_string(), "♕@h7");
+        let san: San = "Nb1d2".parse().expect("valid san");
+        assert_eq!(san.fan().to_string(), "♘b1d2");
+        let san: San = "O-O-O".parse().expect("valid san");
+        assert_eq!(san.fan().to_string(), "O-O-O");
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_structured_roundtrip() {
+        for (text, san) in [
+            (
+                "Nbd7",
+                San::Normal {
+                    role: Role::Knight,
+                    file: Some(File::B),
+                    rank: None,
+                    capture: false,
+                    to: Square::D7,
+                    promotion: None,
+                },
+            ),
+            (
+                "R1xa3",
+                San::Normal {
+                    role: Role::Rook,
+                    file: None,
+                    rank: Some(Rank::First),
+                    capture: true,
+                    to: Square::A3,
+                    promotion: None,
+                },
+            ),
+            (
+                "Qh4xe1",
+                San::Normal {
+                    role: Role::Queen,
+                    file: Some(File::H),
+                    rank: Some(Rank::Fourth),
+                    capture: true,
+                    to: Square::E1,
+                    promotion: None,
+                },
+            ),
+            (
+                "exd8=N",
+                San::Normal {
+                    role: Role::Pawn,
+                    file: Some(File::E),
+                    rank: None,
+                    capture: true,
+                    to: Square::D8,
+                    promotion: Some(Role::Knight),
+                },
+            ),
+            (
+                "b1=Q",
+                San::Normal {
+                    role: Role::Pawn,
+                    file: None,
+                    rank: None,
+                    capture: false,
+                    to: Square::B1,
+                    promotion: Some(Role::Queen),
+                },
+            ),
+            ("O-O-O", San::Castle(CastlingSide::QueenSide)),
+            (
+                "N@f7",
+                San::Put {
+                    role: Role::Knight,
+                    to: Square::F7,
+                },
+            ),
+        ] {
+            assert_eq!(text.parse::<San>().expect("valid san"), san);
+            assert_eq!(san.to_string(), text);
+        }
+    }
+
     #[test]
     fn test_pawn_capture_without_file() {
         let san = "f6".parse::<San>().expect("valid san");
@@ -2,7 +2,7 @@ use core::{convert::identity, num::NonZeroU32};
 
 use crate::{
     attacks, Bitboard, Board, ByCastlingSide, ByColor, ByRole, CastlingMode, CastlingSide, Color,
-    File, FromSetup, PositionError, Rank, RemainingChecks, Square,
+    File, FromSetup, Move, PositionError, Rank, RemainingChecks, Role, Square,
 };
 
 /// A not necessarily legal position.
@@ -150,6 +150,117 @@ impl Setup {
     }
 }
 
+/// Reconstructs the move that was played between two setups one ply apart,
+/// for example as reported by electronic boards.
+///
+/// Recognizes normal moves, captures, promotions, en passant, castling
+/// (including Chess960 castling, based on the castling rights of `before`),
+/// and drops. Returns `None` if `after` is not the board of `before`
+/// with exactly one move of the side to move applied. The legality of the
+/// move is not checked.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{fen::Fen, infer_move, Move, Role, Square};
+///
+/// let before = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".parse::<Fen>()?.into_setup();
+/// let after = "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1".parse::<Fen>()?.into_setup();
+/// assert_eq!(
+///     infer_move(&before, &after),
+///     Some(Move::Castle {
+///         king: Square::E1,
+///         rook: Square::H1,
+///     })
+/// );
+/// # Ok::<_, shakmaty::fen::ParseFenError>(())
+/// ```
+pub fn infer_move(before: &Setup, after: &Setup) -> Option<Move> {
+    let us = before.turn;
+    if after.turn != !us {
+        return None;
+    }
+
+    let left = before.board.by_color(us) & !after.board.by_color(us);
+    let arrived = after.board.by_color(us) & !before.board.by_color(us);
+
+    // Try castling first, because a Chess960 castling move may look like a
+    // plain rook or king move.
+    let castling = before.board.king_of(us).and_then(|king| {
+        (before.castling_rights & before.board.rooks() & before.board.by_color(us))
+            .into_iter()
+            .map(|rook| Move::Castle { king, rook })
+            .find(|m| play_on_board(&before.board, us, m) == after.board)
+    });
+    if castling.is_some() {
+        return castling;
+    }
+
+    let candidate = match (left.single_square(), arrived.single_square()) {
+        (Some(from), Some(to)) => {
+            let role = before.board.role_at(from)?;
+            let capture = before.board.role_at(to);
+            if role == Role::Pawn && capture.is_none() && from.file() != to.file() {
+                Move::EnPassant { from, to }
+            } else {
+                let promotion = after.board.role_at(to).filter(|&r| r != role);
+                let backrank = to.relative_rank(us) == Rank::Eighth;
+                if promotion.is_some() != (role == Role::Pawn && backrank) {
+                    // Only pawns promote, and they must on the back rank.
+                    return None;
+                }
+                Move::Normal {
+                    role,
+                    from,
+                    capture,
+                    to,
+                    promotion,
+                }
+            }
+        }
+        (None, Some(to)) if left.is_empty() => Move::Put {
+            role: after.board.role_at(to)?,
+            to,
+        },
+        _ => return None,
+    };
+
+    (play_on_board(&before.board, us, &candidate) == after.board).then_some(candidate)
+}
+
+/// Applies the piece movements of `m` by `color` to a copy of `board`.
+fn play_on_board(board: &Board, color: Color, m: &Move) -> Board {
+    let mut board = board.clone();
+    match *m {
+        Move::Normal {
+            role,
+            from,
+            to,
+            promotion,
+            ..
+        } => {
+            board.discard_piece_at(from);
+            board.set_piece_at(to, promotion.unwrap_or(role).of(color));
+        }
+        Move::EnPassant { from, to } => {
+            board.discard_piece_at(from);
+            board.discard_piece_at(Square::from_coords(to.file(), from.rank()));
+            board.set_piece_at(to, Role::Pawn.of(color));
+        }
+        Move::Castle { king, rook } => {
+            let side = CastlingSide::from_queen_side(rook < king);
+            board.discard_piece_at(king);
+            board.discard_piece_at(rook);
+            board.set_piece_at(side.king_to(color), Role::King.of(color));
+            board.set_piece_at(side.rook_to(color), Role::Rook.of(color));
+        }
+        Move::Put { role, to } => {
+            board.set_piece_at(to, role.of(color));
+        }
+    }
+    board
+}
+
 impl Default for Setup {
     fn default() -> Setup {
         Setup::initial()
@@ -426,3 +537,125 @@ impl EnPassant {
         self.0.xor(Square::A2)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fen::Fen, uci::UciMove, Chess, EnPassantMode, Position};
+
+    fn infer(fen: &str, uci: &str) -> (Move, Option<Move>) {
+        let pos: Chess = fen
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Chess960)
+            .expect("legal position");
+        let m = uci
+            .parse::<UciMove>()
+            .expect("valid uci")
+            .to_move(&pos)
+            .expect("legal uci");
+        let before = pos.clone().into_setup(EnPassantMode::Legal);
+        let after = pos
+            .play(&m)
+            .expect("legal move")
+            .into_setup(EnPassantMode::Legal);
+        (m, infer_move(&before, &after))
+    }
+
+    #[test]
+    fn test_infer_move() {
+        for (fen, uci) in [
+            // Normal moves and captures.
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "e2e4",
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                "g1f3",
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+                "e4d5",
+            ),
+            // Promotions, with and without capture.
+            ("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"),
+            ("1r2k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7b8n"),
+            // En passant and a normal pawn capture from the same square.
+            ("4k3/8/8/2pPp3/8/8/8/4K3 w - c6 0 1", "d5c6"),
+            ("4k3/8/4p3/2pP4/8/8/8/4K3 w - c6 0 1", "d5e6"),
+            ("4k3/8/8/8/3pP3/8/8/4K3 b - e3 0 1", "d4e3"),
+            // Castling.
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1h1"),
+            ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1a1"),
+            ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8a8"),
+            // Chess960 castling where the king does not move.
+            ("4k3/8/8/8/8/8/8/6KR w H - 0 1", "g1h1"),
+            // Chess960 castling where the rook does not move.
+            ("4k3/8/8/8/8/8/8/4KR2 w F - 0 1", "e1f1"),
+        ] {
+            let (m, inferred) = infer(fen, uci);
+            assert_eq!(inferred, Some(m), "{fen} {uci}");
+        }
+    }
+
+    #[test]
+    fn test_infer_move_invalid() {
+        let before = Setup::initial();
+
+        // Not one ply apart.
+        assert_eq!(infer_move(&before, &before), None);
+
+        // Two moves of the same side.
+        let mut after = before.clone();
+        after.board.discard_piece_at(Square::E2);
+        after.board.discard_piece_at(Square::D2);
+        after.board.set_piece_at(Square::E4, Color::White.pawn());
+        after.board.set_piece_at(Square::D4, Color::White.pawn());
+        after.turn = Color::Black;
+        assert_eq!(infer_move(&before, &after), None);
+
+        // Moved piece changes role.
+        let mut after = before.clone();
+        after.board.discard_piece_at(Square::G1);
+        after.board.set_piece_at(Square::F3, Color::White.bishop());
+        after.turn = Color::Black;
+        assert_eq!(infer_move(&before, &after), None);
+
+        // Pawn changes role off the back rank.
+        let mut after = before.clone();
+        after.board.discard_piece_at(Square::E2);
+        after.board.set_piece_at(Square::E4, Color::White.queen());
+        after.turn = Color::Black;
+        assert_eq!(infer_move(&before, &after), None);
+
+        // Pawn reaches the back rank without promoting.
+        let before = Setup {
+            board: "4k3/P7/8/8/8/8/8/4K3".parse().expect("valid board"),
+            ..Setup::empty()
+        };
+        let mut after = before.clone();
+        after.board.discard_piece_at(Square::A7);
+        after.board.set_piece_at(Square::A8, Color::White.pawn());
+        after.turn = Color::Black;
+        assert_eq!(infer_move(&before, &after), None);
+    }
+
+    #[test]
+    fn test_infer_drop() {
+        let before = Setup {
+            board: Board::empty(),
+            ..Setup::empty()
+        };
+        let mut after = before.clone();
+        after.board.set_piece_at(Square::E4, Color::White.knight());
+        after.turn = Color::Black;
+        assert_eq!(
+            infer_move(&before, &after),
+            Some(Move::Put {
+                role: Role::Knight,
+                to: Square::E4,
+            })
+        );
+    }
+}
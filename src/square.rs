@@ -1,13 +1,16 @@
 use core::{
     cmp::max,
     fmt::{self, Write as _},
-    mem,
+    iter, mem,
     num::TryFromIntError,
-    ops::Sub,
-    str,
+    ops::{Bound, RangeBounds, Sub},
+    slice, str,
 };
 
-use crate::util::{out_of_range_error, AppendAscii};
+use crate::{
+    util::{out_of_range_error, AppendAscii},
+    Color,
+};
 
 macro_rules! try_from_int_impl {
     ($type:ty, $lower:expr, $upper:expr, $($t:ty)+) => {
@@ -27,6 +30,36 @@ macro_rules! try_from_int_impl {
     }
 }
 
+macro_rules! range_impl {
+    ($type:ident) => {
+        impl $type {
+            #[doc = concat!("Iterates over the [`", stringify!($type), "`] values in `range`, in ascending order.")]
+            ///
+            /// # Examples
+            ///
+            /// ```
+            #[doc = concat!("use shakmaty::", stringify!($type), ";")]
+            ///
+            #[doc = concat!("let all: Vec<", stringify!($type), "> = ", stringify!($type), "::range(..).collect();")]
+            #[doc = concat!("assert_eq!(all, ", stringify!($type), "::ALL);")]
+            /// ```
+            pub fn range<R: RangeBounds<$type>>(range: R) -> iter::Copied<slice::Iter<'static, $type>> {
+                let start = match range.start_bound() {
+                    Bound::Included(&start) => usize::from(start),
+                    Bound::Excluded(&start) => usize::from(start) + 1,
+                    Bound::Unbounded => 0,
+                };
+                let end = match range.end_bound() {
+                    Bound::Included(&end) => usize::from(end) + 1,
+                    Bound::Excluded(&end) => usize::from(end),
+                    Bound::Unbounded => $type::ALL.len(),
+                };
+                $type::ALL[start..max(start, end)].iter().copied()
+            }
+        }
+    };
+}
+
 /// A file of the chessboard.
 #[allow(missing_docs)]
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -147,6 +180,7 @@ impl fmt::Display for File {
 
 from_enum_as_int_impl! { File, u8 i8 u16 i16 u32 i32 u64 i64 u128 i128 usize isize }
 try_from_int_impl! { File, 0, 8, u8 i8 u16 i16 u32 i32 u64 i64 u128 i128 usize isize }
+range_impl! { File }
 
 /// A rank of the chessboard.
 #[allow(missing_docs)]
@@ -263,6 +297,7 @@ impl fmt::Display for Rank {
 
 from_enum_as_int_impl! { Rank, u8 i8 u16 i16 u32 i32 u64 i64 u128 i128 usize isize }
 try_from_int_impl! { Rank, 0, 8, u8 i8 u16 i16 u32 i32 u64 i64 u128 i128 usize isize }
+range_impl! { Rank }
 
 /// Error when parsing an invalid square name.
 #[derive(Clone, Debug)]
@@ -315,6 +350,29 @@ impl Square {
         unsafe { Square::new_unchecked(index) }
     }
 
+    /// Gets a `Square` from an integer index, or `None` if the index is not
+    /// in the range `0..=63`. This is a `const` alternative to the
+    /// [`TryFrom`] implementations, for example to validate deserialized
+    /// indices.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::Square;
+    ///
+    /// assert_eq!(Square::from_index(63), Some(Square::H8));
+    /// assert_eq!(Square::from_index(64), None);
+    /// ```
+    #[inline]
+    pub const fn from_index(index: u32) -> Option<Square> {
+        if index < 64 {
+            // Safety: Just checked the range.
+            Some(unsafe { Square::new_unchecked(index) })
+        } else {
+            None
+        }
+    }
+
     /// Gets a `Square` from an integer index.
     ///
     /// # Safety
@@ -417,6 +475,39 @@ impl Square {
         (self.file(), self.rank())
     }
 
+    /// Gets the rank from the point of view of `color`, i.e., flipped
+    /// vertically for black.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Color, Rank, Square};
+    ///
+    /// assert_eq!(Square::E7.relative_rank(Color::White), Rank::Seventh);
+    /// assert_eq!(Square::E7.relative_rank(Color::Black), Rank::Second);
+    /// ```
+    #[inline]
+    pub fn relative_rank(self, color: Color) -> Rank {
+        color.relative_rank(self.rank())
+    }
+
+    /// Gets the square from the point of view of `color`, i.e., flipped
+    /// vertically for black.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Color, Square};
+    ///
+    /// assert_eq!(Square::A1.relative_to(Color::White), Square::A1);
+    /// assert_eq!(Square::A1.relative_to(Color::Black), Square::A8);
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn relative_to(self, color: Color) -> Square {
+        color.fold_wb(self, self.flip_vertical())
+    }
+
     /// Calculates the offset from a square index.
     ///
     /// # Examples
@@ -643,6 +734,7 @@ mod all_squares {
 
 from_enum_as_int_impl! { Square, u8 i8 u16 i16 u32 i32 u64 i64 u128 i128 usize isize }
 try_from_int_impl! { Square, 0, 64, u8 i8 u16 i16 u32 i32 u64 i64 u128 i128 usize isize }
+range_impl! { Square }
 
 impl Sub for Square {
     type Output = i32;
@@ -685,6 +777,36 @@ impl fmt::Debug for Square {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_from_index() {
+        assert_eq!(Square::from_index(0), Some(Square::A1));
+        assert_eq!(Square::from_index(63), Some(Square::H8));
+        assert_eq!(Square::from_index(64), None);
+        assert_eq!(Square::from_index(u32::MAX), None);
+        for sq in Square::ALL {
+            assert_eq!(Square::from_index(u32::from(sq)), Some(sq));
+        }
+    }
+
+    #[test]
+    fn test_relative_to_color() {
+        for (square, white, black) in [
+            (Square::A1, Square::A1, Square::A8),
+            (Square::E2, Square::E2, Square::E7),
+            (Square::D4, Square::D4, Square::D5),
+            (Square::H8, Square::H8, Square::H1),
+        ] {
+            assert_eq!(square.relative_to(Color::White), white);
+            assert_eq!(square.relative_to(Color::Black), black);
+            assert_eq!(square.relative_rank(Color::White), white.rank());
+            assert_eq!(square.relative_rank(Color::Black), black.rank());
+        }
+
+        // Seventh rank from each side's perspective.
+        assert_eq!(Square::B7.relative_rank(Color::White), Rank::Seventh);
+        assert_eq!(Square::B2.relative_rank(Color::Black), Rank::Seventh);
+    }
+
     #[test]
     fn test_square() {
         for file in (0..8).map(File::new) {
@@ -696,6 +818,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_range() {
+        assert!(File::range(..).eq(File::ALL));
+        assert!(File::range(File::A..=File::H).eq(File::ALL));
+        assert!(File::range(File::C..File::F).eq([File::C, File::D, File::E]));
+        assert!(File::range(File::G..).eq([File::G, File::H]));
+        assert!(Rank::range(..=Rank::Second).eq([Rank::First, Rank::Second]));
+        assert!(Rank::range((Bound::Excluded(Rank::Seventh), Bound::Unbounded)).eq([Rank::Eighth]));
+        assert_eq!(Rank::range(Rank::Fifth..Rank::Fifth).len(), 0);
+        assert_eq!(Rank::range(Rank::Fifth..Rank::Second).len(), 0);
+        assert!(Square::range(Square::G8..).eq([Square::G8, Square::H8]));
+        assert_eq!(
+            Square::range(Square::A2..Square::A3).next_back(),
+            Some(Square::H2)
+        );
+    }
+
     #[cfg(feature = "nohash-hasher")]
     #[test]
     fn test_nohash_hasher() {
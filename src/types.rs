@@ -12,6 +12,16 @@ use crate::{
 };
 
 /// A piece with [`Color`] and [`Role`].
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Color, Piece, Role};
+///
+/// let Piece { color, role } = Color::Black.rook();
+/// assert_eq!(color, Color::Black);
+/// assert_eq!(role, Role::Rook);
+/// ```
 #[allow(missing_docs)]
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
 pub struct Piece {
@@ -27,6 +37,47 @@ impl Piece {
     pub fn from_char(ch: char) -> Option<Piece> {
         Role::from_char(ch).map(|role| role.of(Color::from_white(32 & ch as u8 == 0)))
     }
+
+    /// Gets the Unicode chess symbol for the piece.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::Color;
+    ///
+    /// assert_eq!(Color::White.knight().figurine(), '♘');
+    /// assert_eq!(Color::Black.knight().figurine(), '♞');
+    /// ```
+    pub const fn figurine(self) -> char {
+        match self.color {
+            Color::White => self.role.figurine(),
+            Color::Black => match self.role {
+                Role::Pawn => '♟',
+                Role::Knight => '♞',
+                Role::Bishop => '♝',
+                Role::Rook => '♜',
+                Role::Queen => '♛',
+                Role::King => '♚',
+            },
+        }
+    }
+
+    /// The same role with the opposite color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::Color;
+    ///
+    /// assert_eq!(Color::White.knight().flip(), Color::Black.knight());
+    /// ```
+    #[must_use]
+    pub const fn flip(self) -> Piece {
+        Piece {
+            color: self.color.other(),
+            role: self.role,
+        }
+    }
 }
 
 /// Information about a move.
@@ -158,6 +209,71 @@ impl Move {
         )
     }
 
+    /// Gets a key for move ordering, where better moves have higher keys.
+    ///
+    /// Captures are ordered by most valuable victim, then least valuable
+    /// attacker (MVV-LVA). Promotions add ten times the value of the
+    /// promoted piece, so that a queen promotion ranks above any capture
+    /// without promotion. All other moves have key `0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Move, Role, Square};
+    ///
+    /// let pxq = Move::Normal {
+    ///     role: Role::Pawn,
+    ///     from: Square::E4,
+    ///     capture: Some(Role::Queen),
+    ///     to: Square::D5,
+    ///     promotion: None,
+    /// };
+    /// let qxp = Move::Normal {
+    ///     role: Role::Queen,
+    ///     from: Square::D1,
+    ///     capture: Some(Role::Pawn),
+    ///     to: Square::D5,
+    ///     promotion: None,
+    /// };
+    /// assert!(pxq.sort_key() > qxp.sort_key());
+    /// assert!(qxp.sort_key() > 0);
+    /// ```
+    pub const fn sort_key(&self) -> i32 {
+        let mut key = 0;
+        if let Some(victim) = self.capture() {
+            key += 10 * victim.value() - self.role() as i32;
+        }
+        if let Some(promotion) = self.promotion() {
+            key += 10 * promotion.value();
+        }
+        key
+    }
+
+    /// Mirrors the move vertically, keeping roles, captures and
+    /// promotions.
+    ///
+    /// Castling and en passant moves are mirrored square by square, so that
+    /// the result is the corresponding move on the mirrored position
+    /// (see [`Setup::mirror()`](crate::Setup::mirror)).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Move, Role, Square};
+    ///
+    /// let m = Move::Castle {
+    ///     king: Square::E1,
+    ///     rook: Square::H1,
+    /// };
+    /// assert_eq!(
+    ///     m.to_mirrored(),
+    ///     Move::Castle {
+    ///         king: Square::E8,
+    ///         rook: Square::H8,
+    ///     }
+    /// );
+    /// ```
+    #[doc(alias = "mirror_vertical")]
     #[must_use]
     pub fn to_mirrored(&self) -> Move {
         match *self {
@@ -313,6 +429,68 @@ mod tests {
         assert!(Role::Queen < Role::King);
     }
 
+    #[test]
+    fn test_piece_flip() {
+        for color in Color::ALL {
+            for role in Role::ALL {
+                let piece = role.of(color);
+                assert_eq!(piece.flip(), role.of(!color));
+                assert_eq!(piece.flip().flip(), piece);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sort_key() {
+        let normal = |role, capture, promotion| Move::Normal {
+            role,
+            from: Square::B7,
+            capture,
+            to: Square::A8,
+            promotion,
+        };
+
+        let mut moves = [
+            normal(Role::Knight, None, None),
+            normal(Role::Queen, Some(Role::Pawn), None),
+            normal(Role::Pawn, Some(Role::Rook), Some(Role::Queen)),
+            normal(Role::Pawn, None, Some(Role::Knight)),
+            normal(Role::Knight, Some(Role::Rook), None),
+            Move::EnPassant {
+                from: Square::E5,
+                to: Square::D6,
+            },
+            normal(Role::Pawn, None, Some(Role::Queen)),
+            normal(Role::Bishop, Some(Role::Rook), None),
+            Move::Castle {
+                king: Square::E1,
+                rook: Square::H1,
+            },
+        ];
+        moves.sort_by_key(|m| -m.sort_key());
+
+        assert_eq!(
+            moves,
+            [
+                normal(Role::Pawn, Some(Role::Rook), Some(Role::Queen)),
+                normal(Role::Pawn, None, Some(Role::Queen)),
+                normal(Role::Knight, Some(Role::Rook), None),
+                normal(Role::Bishop, Some(Role::Rook), None),
+                normal(Role::Pawn, None, Some(Role::Knight)),
+                Move::EnPassant {
+                    from: Square::E5,
+                    to: Square::D6,
+                },
+                normal(Role::Queen, Some(Role::Pawn), None),
+                normal(Role::Knight, None, None),
+                Move::Castle {
+                    king: Square::E1,
+                    rook: Square::H1,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_move_size() {
         assert!(mem::size_of::<Move>() <= 8);
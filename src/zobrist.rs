@@ -19,19 +19,152 @@
 //! let pos = Chess::default();
 //! assert_eq!(pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal), Zobrist64(0x463b96181691fc9c));
 //! ```
+//!
+//! Use [`Zobrist`] to maintain the hash incrementally while playing moves:
+//!
+//! ```
+//! use shakmaty::{Chess, EnPassantMode, Position, zobrist::{Zobrist, Zobrist64, ZobristHash}};
+//! # use shakmaty::{Move, Role, Square};
+//!
+//! let mut pos: Zobrist<Chess, Zobrist64> = Zobrist::new(Chess::default());
+//! # let m = Move::Normal { role: Role::Pawn, from: Square::E2, to: Square::E4, capture: None, promotion: None };
+//! pos.play_unchecked(&m);
+//! assert_eq!(pos.hash(), Zobrist64(0x823c9b50fd114196));
+//! ```
+//!
+//! # Hash width
+//!
+//! Hashes are available with 8, 16, 32, 64, and 128 bits. All widths share
+//! one table of 128 bit keys: [`Zobrist128`] uses the full keys, and each
+//! narrower width uses only their lower bits, so a narrower hash is the
+//! truncation of a wider one. [`Zobrist128`] is useful to key large position
+//! databases, where 64 bit collisions are too likely:
+//!
+//! ```
+//! use shakmaty::{Chess, zobrist::{Zobrist, Zobrist64, Zobrist128}};
+//!
+//! let pos: Zobrist<Chess, Zobrist128> = Zobrist::default();
+//! assert_eq!(pos.hash(), Zobrist128(0x3d71fe83987aab73463b96181691fc9c));
+//! assert_eq!(Zobrist64::from(pos.hash()), Zobrist64(0x463b96181691fc9c));
+//! ```
+//!
+//! # Polyglot
+//!
+//! For standard chess, [`Zobrist64`] hashes are compatible with the keys of
+//! Polyglot opening books, when computed with [`EnPassantMode::PseudoLegal`].
+//! [`Zobrist::hash()`] uses [`EnPassantMode::Legal`] instead, which differs
+//! only in rare positions where an en passant capture is pseudo-legal but
+//! not legal. Use [`Zobrist::polyglot_hash()`] to probe books.
+//!
+//! # Testing
+//!
+//! [`Zobrist`] updates the hash from the changes it observes in the wrapped
+//! [`Position`]. With the `verify` feature, `verify_incremental()` plays
+//! random games of a position type, comparing the incremental hashes with
+//! hashes computed from scratch, for example to test new variants.
 
+#[cfg(all(feature = "alloc", target_has_atomic = "64"))]
+use core::sync::atomic::{AtomicU64, Ordering};
 use core::{
     fmt,
     hash::{Hash, Hasher},
-    ops::{BitXor, BitXorAssign},
+    marker::PhantomData,
+    num::NonZeroU32,
+    ops::{BitXor, BitXorAssign, Deref},
 };
+#[cfg(feature = "std")]
+use std::{collections::HashMap, hash::BuildHasher};
 
 use crate::{
-    Board, ByColor, ByRole, CastlingSide, Color, EnPassantMode, File, Piece, Position,
-    RemainingChecks, Role, Square,
+    attacks,
+    fen::Epd,
+    position::ChessUndo,
+    san::{San, SanError},
+    Bitboard, Board, ByColor, ByRole, Castles, CastlingMode, CastlingSide, Chess, Color,
+    EnPassantMode, File, FromSetup, Move, MoveList, Outcome, Piece, Position, PositionError,
+    PositionErrorKinds, RemainingChecks, Role, Setup, Square,
 };
 
 /// Integer type that can be returned as a Zobrist hash.
+///
+/// Implement this trait to hash with custom keys, for example to stay
+/// consistent with external tooling, or to reproduce research setups with
+/// seeded keys. [`Zobrist`] and [`ZobristHash`] work with any
+/// implementation.
+///
+/// # Examples
+///
+/// ```
+/// use core::ops::BitXorAssign;
+///
+/// use shakmaty::{
+///     uci::UciMove,
+///     zobrist::{Zobrist, ZobristHash, ZobristValue},
+///     CastlingSide, Chess, Color, EnPassantMode, File, Piece, Position, RemainingChecks, Role,
+///     Square,
+/// };
+///
+/// /// Keys derived from a seed with SplitMix64.
+/// #[derive(Default, Copy, Clone, PartialEq, Debug)]
+/// struct Seeded(u64);
+///
+/// impl Seeded {
+///     const SEED: u64 = 42;
+///
+///     fn key(index: u64) -> Seeded {
+///         let mut z = Seeded::SEED.wrapping_add(index.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+///         z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+///         z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+///         Seeded(z ^ (z >> 31))
+///     }
+/// }
+///
+/// impl BitXorAssign for Seeded {
+///     fn bitxor_assign(&mut self, rhs: Seeded) {
+///         self.0 ^= rhs.0;
+///     }
+/// }
+///
+/// impl ZobristValue for Seeded {
+///     fn zobrist_for_piece(square: Square, piece: Piece) -> Seeded {
+///         let piece = piece.color as u64 * 6 + (piece.role as u64 - 1);
+///         Seeded::key(piece * 64 + u64::from(square))
+///     }
+///     fn zobrist_for_white_turn() -> Seeded {
+///         Seeded::key(768)
+///     }
+///     fn zobrist_for_castling_right(color: Color, side: CastlingSide) -> Seeded {
+///         Seeded::key(769 + color as u64 * 2 + side as u64)
+///     }
+///     fn zobrist_for_en_passant_file(file: File) -> Seeded {
+///         Seeded::key(773 + u64::from(file))
+///     }
+///     fn zobrist_for_remaining_checks(color: Color, remaining: RemainingChecks) -> Seeded {
+///         Seeded::key(781 + color as u64 * 4 + u64::from(remaining))
+///     }
+///     fn zobrist_for_promoted(square: Square) -> Seeded {
+///         Seeded::key(789 + u64::from(square))
+///     }
+///     fn zobrist_for_pocket(color: Color, role: Role, pieces: u8) -> Seeded {
+///         let pocket = color as u64 * 6 + (role as u64 - 1);
+///         Seeded::key(853 + pocket * 256 + u64::from(pieces))
+///     }
+/// }
+///
+/// let mut pos: Zobrist<Chess, Seeded> = Zobrist::default();
+/// let m = "e2e4".parse::<UciMove>()?.to_move(&pos)?;
+/// pos.play_unchecked(&m);
+/// assert_eq!(pos.hash(), pos.zobrist_hash::<Seeded>(EnPassantMode::Legal));
+///
+/// # use shakmaty::uci::{IllegalUciMoveError, ParseUciMoveError};
+/// # #[derive(Debug)] struct CommonError;
+/// # impl From<ParseUciMoveError> for CommonError { fn from(_: ParseUciMoveError) -> Self { Self } }
+/// # impl From<IllegalUciMoveError> for CommonError { fn from(_: IllegalUciMoveError) -> Self { Self } }
+/// # Ok::<_, CommonError>(())
+/// ```
+///
+/// Alternatively, generate a complete key table at runtime with
+/// [`ZobristTables::from_seed()`] and hash with [`Keyed`] values.
 pub trait ZobristValue: BitXorAssign + Default + Copy {
     fn zobrist_for_piece(square: Square, piece: Piece) -> Self;
     fn zobrist_for_white_turn() -> Self;
@@ -40,6 +173,18 @@ pub trait ZobristValue: BitXorAssign + Default + Copy {
     fn zobrist_for_remaining_checks(color: Color, remaining: RemainingChecks) -> Self;
     fn zobrist_for_promoted(square: Square) -> Self;
     fn zobrist_for_pocket(color: Color, role: Role, pieces: u8) -> Self;
+
+    /// Key for the `n`-th piece of its kind (counting from 0), used for the
+    /// [material hash](Zobrist::material_hash()).
+    ///
+    /// The default reuses the piece key of the square with index `n`, so
+    /// material hashes can collide with piece placement hashes, and the two
+    /// must not be stored in the same table. The provided implementations
+    /// swap the halves of the 128 bit piece keys (or rotate the keys of
+    /// [`Keyed`]) instead, to derive a separate key space.
+    fn zobrist_for_material(piece: Piece, n: usize) -> Self {
+        Self::zobrist_for_piece(Square::new(n as u32), piece)
+    }
 }
 
 macro_rules! zobrist_value_impl {
@@ -943,6 +1088,15 @@ macro_rules! zobrist_value_impl {
                 $t(PIECE_MASKS.get(piece.color).get(piece.role)[usize::from(square)])
             }
 
+            #[inline]
+            fn zobrist_for_material(piece: Piece, n: usize) -> $t {
+                // Swap the halves of the 128 bit piece key before
+                // truncating, so that narrower keys remain truncations of
+                // wider keys.
+                let key = Zobrist128::zobrist_for_piece(Square::new(n as u32), piece);
+                $t(key.0.rotate_left(64) as $proxy)
+            }
+
             #[inline]
             fn zobrist_for_white_turn() -> $t {
                 #[allow(overflowing_literals)]
@@ -1313,6 +1467,263 @@ impl From<Zobrist16> for Zobrist8 {
     }
 }
 
+/// The constants used for Zobrist hashing, as returned by
+/// [`zobrist_tables()`], or generated with [`ZobristTables::from_seed()`].
+///
+/// Useful to build compatible hashers outside of this crate, or to debug
+/// collisions. Hashes of standard chess positions are the exclusive or of
+/// the entries for all pieces, castling rights, the en passant file (if a
+/// legal en passant capture is available), and the white turn (if it is
+/// white to move). Variants additionally include the entries for remaining
+/// checks, promoted pieces, and pockets.
+#[derive(Clone, Debug)]
+pub struct ZobristTables<V> {
+    /// Entries by piece color, role, and square.
+    pub piece_square: ByColor<ByRole<[V; 64]>>,
+    /// Entries by color and castling side, indexed by [`CastlingSide`] as
+    /// `usize`.
+    pub castling: ByColor<[V; 2]>,
+    /// Entries by en passant file.
+    pub en_passant: [V; 8],
+    /// Entry for white to move.
+    pub white_turn: V,
+    /// Entries by color and number of [`RemainingChecks`].
+    pub remaining_checks: ByColor<[V; 4]>,
+    /// Entries by square of a promoted piece.
+    pub promoted: [V; 64],
+    /// Entries by color, role, and bit of the number of pieces in the
+    /// pocket. The entry for a number of pieces is the exclusive or of the
+    /// entries for each of its set bits.
+    pub pockets: ByColor<ByRole<[V; 8]>>,
+}
+
+impl ZobristTables<Zobrist64> {
+    /// Generates independent keys from `seed`, for example to reproduce
+    /// research setups, or to hash with keys that are consistent with
+    /// external tooling.
+    ///
+    /// To hash with the generated keys, make them available as a
+    /// [`ZobristKeys`] implementation and use [`Keyed`] values.
+    pub fn from_seed(seed: u64) -> ZobristTables<Zobrist64> {
+        // SplitMix64.
+        let mut state = seed;
+        let mut key = || {
+            state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            Zobrist64(z ^ (z >> 31))
+        };
+
+        ZobristTables {
+            piece_square: ByColor::new_with(|_| ByRole::new_with(|_| [(); 64].map(|()| key()))),
+            castling: ByColor::new_with(|_| [key(), key()]),
+            en_passant: [(); 8].map(|()| key()),
+            white_turn: key(),
+            remaining_checks: ByColor::new_with(|_| [(); 4].map(|()| key())),
+            promoted: [(); 64].map(|()| key()),
+            pockets: ByColor::new_with(|_| ByRole::new_with(|_| [(); 8].map(|()| key()))),
+        }
+    }
+}
+
+/// Collects the constants used for Zobrist hashing with values of type `V`.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{
+///     zobrist::{zobrist_tables, Zobrist64, ZobristValue},
+///     Color, Role, Square,
+/// };
+///
+/// let tables = zobrist_tables::<Zobrist64>();
+/// assert_eq!(
+///     tables.piece_square.white.knight[usize::from(Square::G1)],
+///     Zobrist64::zobrist_for_piece(Square::G1, Color::White.knight())
+/// );
+/// ```
+pub fn zobrist_tables<V: ZobristValue>() -> ZobristTables<V> {
+    ZobristTables {
+        piece_square: ByColor::new_with(|color| {
+            ByRole::new_with(|role| Square::ALL.map(|sq| V::zobrist_for_piece(sq, role.of(color))))
+        }),
+        castling: ByColor::new_with(|color| {
+            [CastlingSide::KingSide, CastlingSide::QueenSide]
+                .map(|side| V::zobrist_for_castling_right(color, side))
+        }),
+        en_passant: File::ALL.map(V::zobrist_for_en_passant_file),
+        white_turn: V::zobrist_for_white_turn(),
+        remaining_checks: ByColor::new_with(|color| {
+            [0, 1, 2, 3].map(|n| V::zobrist_for_remaining_checks(color, RemainingChecks::new(n)))
+        }),
+        promoted: Square::ALL.map(V::zobrist_for_promoted),
+        pockets: ByColor::new_with(|color| {
+            ByRole::new_with(|role| {
+                [0, 1, 2, 3, 4, 5, 6, 7].map(|bit| V::zobrist_for_pocket(color, role, 1 << bit))
+            })
+        }),
+    }
+}
+
+/// A key table for [`Keyed`] hashes.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::OnceLock;
+///
+/// use shakmaty::{
+///     zobrist::{Keyed, Zobrist, Zobrist64, ZobristKeys, ZobristTables},
+///     Chess, EnPassantMode, Position,
+/// };
+///
+/// struct Seeded;
+///
+/// impl ZobristKeys for Seeded {
+///     fn tables() -> &'static ZobristTables<Zobrist64> {
+///         static TABLES: OnceLock<ZobristTables<Zobrist64>> = OnceLock::new();
+///         TABLES.get_or_init(|| ZobristTables::from_seed(42))
+///     }
+/// }
+///
+/// let pos: Zobrist<Chess, Keyed<Seeded>> = Zobrist::default();
+/// let builtin: Zobrist<Chess, Zobrist64> = Zobrist::default();
+/// assert_ne!(pos.hash().into_inner(), builtin.hash());
+/// ```
+pub trait ZobristKeys {
+    /// Gets the key table.
+    fn tables() -> &'static ZobristTables<Zobrist64>;
+}
+
+/// A [`ZobristValue`] with 64 bits, computed with the keys of `K` instead
+/// of the built-in keys.
+pub struct Keyed<K> {
+    hash: Zobrist64,
+    keys: PhantomData<fn() -> K>,
+}
+
+impl<K> Keyed<K> {
+    /// Wraps a hash computed with the keys of `K`.
+    pub const fn new(hash: Zobrist64) -> Keyed<K> {
+        Keyed {
+            hash,
+            keys: PhantomData,
+        }
+    }
+
+    /// Gets the hash.
+    pub const fn into_inner(self) -> Zobrist64 {
+        self.hash
+    }
+}
+
+impl<K> Default for Keyed<K> {
+    fn default() -> Keyed<K> {
+        Keyed::new(Zobrist64::default())
+    }
+}
+
+impl<K> Clone for Keyed<K> {
+    fn clone(&self) -> Keyed<K> {
+        *self
+    }
+}
+
+impl<K> Copy for Keyed<K> {}
+
+impl<K> PartialEq for Keyed<K> {
+    fn eq(&self, other: &Keyed<K>) -> bool {
+        self.hash == other.hash
+    }
+}
+
+impl<K> Eq for Keyed<K> {}
+
+impl<K> Hash for Keyed<K> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state);
+    }
+}
+
+impl<K> fmt::Debug for Keyed<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Keyed({:#x})", self.hash.0)
+    }
+}
+
+impl<K> fmt::UpperHex for Keyed<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.hash, f)
+    }
+}
+
+impl<K> fmt::LowerHex for Keyed<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.hash, f)
+    }
+}
+
+impl<K> fmt::Octal for Keyed<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Octal::fmt(&self.hash, f)
+    }
+}
+
+impl<K> fmt::Binary for Keyed<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Binary::fmt(&self.hash, f)
+    }
+}
+
+impl<K> BitXorAssign for Keyed<K> {
+    fn bitxor_assign(&mut self, rhs: Keyed<K>) {
+        self.hash ^= rhs.hash;
+    }
+}
+
+impl<K: ZobristKeys> ZobristValue for Keyed<K> {
+    fn zobrist_for_piece(square: Square, piece: Piece) -> Keyed<K> {
+        Keyed::new(K::tables().piece_square.get(piece.color).get(piece.role)[usize::from(square)])
+    }
+
+    fn zobrist_for_material(piece: Piece, n: usize) -> Keyed<K> {
+        let key = K::tables().piece_square.get(piece.color).get(piece.role)[n];
+        Keyed::new(Zobrist64(key.0.rotate_left(32)))
+    }
+
+    fn zobrist_for_white_turn() -> Keyed<K> {
+        Keyed::new(K::tables().white_turn)
+    }
+
+    fn zobrist_for_castling_right(color: Color, side: CastlingSide) -> Keyed<K> {
+        Keyed::new(K::tables().castling.get(color)[side as usize])
+    }
+
+    fn zobrist_for_en_passant_file(file: File) -> Keyed<K> {
+        Keyed::new(K::tables().en_passant[usize::from(file)])
+    }
+
+    fn zobrist_for_remaining_checks(color: Color, remaining: RemainingChecks) -> Keyed<K> {
+        Keyed::new(K::tables().remaining_checks.get(color)[usize::from(remaining)])
+    }
+
+    fn zobrist_for_promoted(square: Square) -> Keyed<K> {
+        Keyed::new(K::tables().promoted[usize::from(square)])
+    }
+
+    fn zobrist_for_pocket(color: Color, role: Role, pieces: u8) -> Keyed<K> {
+        let entries = K::tables().pockets.get(color).get(role);
+        let mut hash = Zobrist64::default();
+        for (bit, entry) in entries.iter().enumerate() {
+            if pieces & (1 << bit) != 0 {
+                hash ^= entry;
+            }
+        }
+        Keyed::new(hash)
+    }
+}
+
 /// Supports Zobrist hashing.
 pub trait ZobristHash {
     /// Computes the Zobrist hash of the position from scratch. The hash
@@ -1322,54 +1733,58 @@ pub trait ZobristHash {
 
 impl<P: Position> ZobristHash for P {
     fn zobrist_hash<V: ZobristValue>(&self, mode: EnPassantMode) -> V {
-        let mut zobrist = hash_board(self.board());
+        let mut zobrist = board_hash(self.board());
 
-        for sq in self.promoted() {
-            zobrist ^= V::zobrist_for_promoted(sq);
-        }
+        zobrist ^= hash_promoted::<V>(self.promoted());
 
         if let Some(pockets) = self.pockets() {
-            for (color, pocket) in pockets.as_ref().zip_color() {
-                for role in Role::ALL {
-                    zobrist ^= V::zobrist_for_pocket(color, role, *pocket.get(role));
-                }
-            }
+            zobrist ^= hash_pockets::<V>(pockets);
         }
 
         if self.turn() == Color::White {
             zobrist ^= V::zobrist_for_white_turn();
         }
 
-        let castles = self.castles();
-        for color in Color::ALL {
-            for side in CastlingSide::ALL {
-                if castles.has(color, side) {
-                    zobrist ^= V::zobrist_for_castling_right(color, side);
-                }
-            }
-        }
+        zobrist ^= hash_castles::<V>(self.castles());
 
         if let Some(sq) = self.ep_square(mode) {
             zobrist ^= V::zobrist_for_en_passant_file(sq.file());
         }
 
         if let Some(remaining_checks) = self.remaining_checks() {
-            for (color, remaining) in remaining_checks.as_ref().zip_color() {
-                zobrist ^= V::zobrist_for_remaining_checks(color, *remaining);
-            }
+            zobrist ^= hash_remaining_checks::<V>(remaining_checks);
         }
 
         zobrist
     }
 }
 
-fn hash_board<V: ZobristValue>(board: &Board) -> V {
+/// Computes the Zobrist hash of the piece placement on `board`.
+///
+/// This is the component of [`ZobristHash::zobrist_hash()`] that depends only
+/// on the board. It does not include promoted pieces, pockets, turn,
+/// castling rights, en passant square, or remaining checks.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Board, zobrist::{board_hash, Zobrist64}};
+///
+/// let hash: Zobrist64 = board_hash(&Board::new());
+/// assert_ne!(hash, Zobrist64(0));
+/// assert_eq!(board_hash::<Zobrist64>(&Board::empty()), Zobrist64(0));
+/// ```
+pub fn board_hash<V: ZobristValue>(board: &Board) -> V {
+    hash_board_region(board, Bitboard::FULL)
+}
+
+fn hash_board_region<V: ZobristValue>(board: &Board, mask: Bitboard) -> V {
     // Order optimized for cache efficiency.
     let mut zobrist = V::default();
     for role in Role::ALL {
         for color in [Color::Black, Color::White] {
             let piece = role.of(color);
-            for sq in board.by_piece(piece) {
+            for sq in board.by_piece(piece) & mask {
                 zobrist ^= V::zobrist_for_piece(sq, piece);
             }
         }
@@ -1377,118 +1792,3340 @@ fn hash_board<V: ZobristValue>(board: &Board) -> V {
     zobrist
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{fen::Fen, CastlingMode, Chess};
+fn hash_material<V: ZobristValue>(board: &Board) -> V {
+    let mut zobrist = V::default();
+    for color in Color::ALL {
+        for role in Role::ALL {
+            let piece = role.of(color);
+            for n in 0..board.by_piece(piece).count() {
+                zobrist ^= V::zobrist_for_material(piece, n);
+            }
+        }
+    }
+    zobrist
+}
 
-    #[test]
-    fn test_polyglot() {
-        let reference_values = [
-            (
-                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
-                Zobrist64(0x463b96181691fc9c),
-            ),
-            (
-                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
-                Zobrist64(0x823c9b50fd114196),
-            ),
-            (
-                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
-                Zobrist64(0x0756b94461c50fb0),
-            ),
-            (
-                "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
-                Zobrist64(0x662fafb965db29d4),
-            ),
-            (
-                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
-                Zobrist64(0x22a48b5a8e47ff78),
-            ),
-            (
-                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 1 3",
-                Zobrist64(0x652a607ca3f242c1),
-            ),
-            (
-                "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 2 4",
-                Zobrist64(0x00fdd303c946bdd9),
-            ),
-            (
-                "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
-                Zobrist64(0x3c8123ea7b067637),
-            ),
-            (
-                "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 1 4",
-                Zobrist64(0x5c3f9b829b279560),
-            ),
-        ];
+fn hash_promoted<V: ZobristValue>(promoted: Bitboard) -> V {
+    // Only Crazyhouse tracks promoted pieces. For all other positions this
+    // is empty, so no keys are looked up and the hash is unaffected.
+    let mut zobrist = V::default();
+    for sq in promoted {
+        zobrist ^= V::zobrist_for_promoted(sq);
+    }
+    zobrist
+}
 
-        for (fen, expected) in reference_values {
-            let pos: Chess = fen
-                .parse::<Fen>()
-                .expect("valid fen")
-                .into_position(CastlingMode::Standard)
-                .expect("legal position");
+fn hash_pockets<V: ZobristValue>(pockets: &ByColor<ByRole<u8>>) -> V {
+    let mut zobrist = V::default();
+    for (color, pocket) in pockets.as_ref().zip_color() {
+        for role in Role::ALL {
+            zobrist ^= V::zobrist_for_pocket(color, role, *pocket.get(role));
+        }
+    }
+    zobrist
+}
 
-            assert_eq!(
-                pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
-                expected,
-                "{}",
-                fen
-            );
+fn hash_castles<V: ZobristValue>(castles: &Castles) -> V {
+    let mut zobrist = V::default();
+    for color in Color::ALL {
+        for side in CastlingSide::ALL {
+            if castles.has(color, side) {
+                zobrist ^= V::zobrist_for_castling_right(color, side);
+            }
         }
     }
+    zobrist
+}
 
-    #[cfg(feature = "variant")]
-    #[test]
-    fn test_variants_not_distinguished() {
-        // Useful when indexing a table of opening names by Zorbist hash.
-        let chess: Zobrist128 = Chess::default().zobrist_hash(EnPassantMode::Legal);
-        let crazyhouse = crate::variant::Crazyhouse::default().zobrist_hash(EnPassantMode::Legal);
-        let three_check = crate::variant::ThreeCheck::default().zobrist_hash(EnPassantMode::Legal);
-        let king_of_the_hill =
-            crate::variant::KingOfTheHill::default().zobrist_hash(EnPassantMode::Legal);
-        assert_eq!(chess, crazyhouse);
-        assert_eq!(chess, three_check);
-        assert_eq!(chess, king_of_the_hill);
+fn hash_remaining_checks<V: ZobristValue>(remaining_checks: &ByColor<RemainingChecks>) -> V {
+    let mut zobrist = V::default();
+    for (color, remaining) in remaining_checks.as_ref().zip_color() {
+        zobrist ^= V::zobrist_for_remaining_checks(color, *remaining);
+    }
+    zobrist
+}
+
+/// Incremental state maintained alongside the hash of a [`Zobrist`]
+/// position, for example material or piece-square scores in an engine.
+///
+/// Pass the state to [`Zobrist::play_unchecked_with()`] to receive the same
+/// piece additions and removals that are used to update the hash.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, Piece, Position, Square, zobrist::{IncrementalState, Zobrist}};
+///
+/// struct PieceCount(u32);
+///
+/// impl IncrementalState for PieceCount {
+///     fn on_add(&mut self, _sq: Square, _piece: Piece) {
+///         self.0 += 1;
+///     }
+///
+///     fn on_remove(&mut self, _sq: Square, _piece: Piece) {
+///         self.0 -= 1;
+///     }
+/// }
+///
+/// let mut pos: Zobrist<Chess> = Zobrist::default();
+/// let mut count = PieceCount(pos.board().occupied().count() as u32);
+/// for m in ["e2e4", "d7d5", "e4d5"] {
+///     let m = m.parse::<shakmaty::uci::UciMove>().unwrap().to_move(&pos).unwrap();
+///     pos.play_unchecked_with(&m, &mut count);
+/// }
+/// assert_eq!(count.0, 31);
+/// ```
+pub trait IncrementalState {
+    /// Called when `piece` is added to `sq`.
+    fn on_add(&mut self, sq: Square, piece: Piece);
+
+    /// Called when `piece` is removed from `sq`.
+    fn on_remove(&mut self, sq: Square, piece: Piece);
+
+    /// Called once per move, after the board has been updated, when the
+    /// side to move changes.
+    fn on_side_flip(&mut self) {}
+}
+
+/// No incremental state.
+impl IncrementalState for () {
+    fn on_add(&mut self, _sq: Square, _piece: Piece) {}
+
+    fn on_remove(&mut self, _sq: Square, _piece: Piece) {}
+}
+
+/// A [`Position`] wrapper that incrementally maintains the Zobrist hash of
+/// the wrapped position.
+///
+/// The hash is equivalent to
+/// [`ZobristHash::zobrist_hash()`] with [`EnPassantMode::Legal`], but is
+/// updated on [`Position::play_unchecked()`] (and therefore also on
+/// [`Position::play()`]) rather than computed from scratch.
+///
+/// The halfmove clock and fullmove number are intentionally not part of
+/// the hash, so that repeated positions have equal hashes, regardless of
+/// the move counters.
+#[derive(Clone, Debug)]
+pub struct Zobrist<P, V = Zobrist64> {
+    pos: P,
+    zobrist: V,
+    pawn_zobrist: V,
+    material_zobrist: V,
+    // The legal en passant square, which is part of the hash.
+    ep_square: Option<Square>,
+}
+
+impl<P: Position, V: ZobristValue> Zobrist<P, V> {
+    /// Wraps a position, computing its initial hash from scratch.
+    pub fn new(pos: P) -> Zobrist<P, V> {
+        let zobrist = pos.zobrist_hash(EnPassantMode::Legal);
+        let pawn_zobrist = hash_board_region(pos.board(), pos.board().pawns());
+        let material_zobrist = hash_material(pos.board());
+        let ep_square = pos.legal_ep_square();
+        Zobrist {
+            pos,
+            zobrist,
+            pawn_zobrist,
+            material_zobrist,
+            ep_square,
+        }
     }
 
-    #[test]
-    fn test_full_pockets() {
-        // 8/8/8/7k/8/8/3K4/8[ppppppppppppppppnnnnbbbbrrrrqq] w - - 0 54
-        for color in Color::ALL {
-            assert_ne!(
-                Zobrist64::zobrist_for_pocket(color, Role::Pawn, 16),
-                Zobrist64::zobrist_for_pocket(color, Role::Pawn, 15)
-            );
-            assert_ne!(
-                Zobrist64::zobrist_for_pocket(color, Role::Queen, 2),
-                Zobrist64::zobrist_for_pocket(color, Role::Queen, 1)
-            );
+    /// Gets the current Zobrist hash of the position.
+    pub fn hash(&self) -> V {
+        self.zobrist
+    }
+
+    /// Gets the key for repetition detection. This is the same as
+    /// [`Zobrist::hash()`]: it covers the piece placement, turn, castling
+    /// rights, legal en passant square, and any variant specific state, but
+    /// not the halfmove clock and fullmove number.
+    ///
+    /// Positions with equal keys are considered repetitions of each other
+    /// (barring hash collisions).
+    pub fn repetition_key(&self) -> V {
+        self.zobrist
+    }
+
+    /// Gets the incrementally maintained hash of the pawn structure, for
+    /// example to key pawn hash tables.
+    ///
+    /// This covers only the pawns of both sides, using the same keys as the
+    /// full hash, so that it equals
+    /// [`region_hash()`](Zobrist::region_hash()) of all pawns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Position, uci::UciMove, zobrist::{Zobrist, Zobrist64}};
+    ///
+    /// let mut pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+    /// let pawn_hash = pos.pawn_hash();
+    ///
+    /// let m = "g1f3".parse::<UciMove>()?.to_move(&pos)?;
+    /// pos.play_unchecked(&m);
+    /// assert_eq!(pos.pawn_hash(), pawn_hash);
+    ///
+    /// let m = "e7e5".parse::<UciMove>()?.to_move(&pos)?;
+    /// pos.play_unchecked(&m);
+    /// assert_ne!(pos.pawn_hash(), pawn_hash);
+    ///
+    /// # use shakmaty::uci::{IllegalUciMoveError, ParseUciMoveError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseUciMoveError> for CommonError { fn from(_: ParseUciMoveError) -> Self { Self } }
+    /// # impl From<IllegalUciMoveError> for CommonError { fn from(_: IllegalUciMoveError) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    pub fn pawn_hash(&self) -> V {
+        self.pawn_zobrist
+    }
+
+    /// Gets the incrementally maintained hash of the material on the board,
+    /// i.e., the number of pieces of each color and role, regardless of
+    /// their placement. Useful to key material tables or to look up
+    /// specialized endgame evaluations.
+    ///
+    /// Pieces in pockets are not included.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{fen::Fen, CastlingMode, Chess, zobrist::{Zobrist, Zobrist64}};
+    ///
+    /// let a: Zobrist<Chess, Zobrist64> = "4k3/8/8/8/8/8/3R4/4K3 w - - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// let b: Zobrist<Chess, Zobrist64> = "4k3/8/8/8/8/8/8/R3K3 b - - 0 1"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// assert_eq!(a.material_hash(), b.material_hash());
+    /// assert_ne!(a.hash(), b.hash());
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    pub fn material_hash(&self) -> V {
+        self.material_zobrist
+    }
+
+    /// Computes the piece placement hash of only the squares in `mask`, for
+    /// example to key caches of evaluation features that depend on part of
+    /// the board, like pawn storms on the king side.
+    ///
+    /// The hash uses the same keys as the full hash, so that
+    /// `region_hash(Bitboard::FULL)` equals [`board_hash()`] of the
+    /// position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Bitboard, Chess, File, zobrist::{Zobrist, Zobrist64}};
+    ///
+    /// let pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+    /// let kingside = Bitboard::from_file(File::F) | Bitboard::from_file(File::G) | Bitboard::from_file(File::H);
+    /// assert_ne!(pos.region_hash(kingside), pos.region_hash(Bitboard::FULL));
+    /// assert_eq!(pos.region_hash(Bitboard::EMPTY), Zobrist64(0));
+    /// ```
+    pub fn region_hash(&self, mask: Bitboard) -> V {
+        hash_board_region(self.pos.board(), mask)
+    }
+
+    /// Gets the hash with [`EnPassantMode::PseudoLegal`] rather than
+    /// [`EnPassantMode::Legal`]. For [`Zobrist64`] and standard chess, this
+    /// is the key used in Polyglot opening books.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{
+    ///     fen::Fen,
+    ///     zobrist::{Zobrist, Zobrist64},
+    ///     CastlingMode, Chess,
+    /// };
+    ///
+    /// // The en passant capture is not legal, because the king would be
+    /// // exposed to the rook.
+    /// let pos: Zobrist<Chess, Zobrist64> = "8/8/8/K1pP3r/8/8/8/4k3 w - c6 0 1"
+    ///     .parse::<Fen>()?
+    ///     .into_position(CastlingMode::Standard)?;
+    /// assert_ne!(pos.polyglot_hash(), pos.hash());
+    ///
+    /// # use shakmaty::{fen::ParseFenError, PositionError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseFenError> for CommonError { fn from(_: ParseFenError) -> Self { Self } }
+    /// # impl<P> From<PositionError<P>> for CommonError { fn from(_: PositionError<P>) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    pub fn polyglot_hash(&self) -> V {
+        let mut zobrist = self.zobrist;
+        if self.ep_square.is_none() {
+            if let Some(sq) = self.pos.pseudo_legal_ep_square() {
+                zobrist ^= V::zobrist_for_en_passant_file(sq.file());
+            }
         }
+        zobrist
     }
 
-    #[cfg(feature = "nohash-hasher")]
-    #[test]
-    fn test_nohash_hasher() {
-        let mut hasher = nohash_hasher::NoHashHasher::<Zobrist128>::default();
-        Zobrist128(128).hash(&mut hasher);
-        assert_eq!(hasher.finish(), 128);
+    /// Computes the hash of the position after a null move, i.e., with the
+    /// turn swapped and en passant rights discarded, without modifying the
+    /// position.
+    ///
+    /// This is useful for probing transposition tables during null move
+    /// pruning. Note that the hash is computed even if it is not actually
+    /// possible to swap turns, for example when in check.
+    pub fn hash_after_null_move(&self) -> V {
+        let mut zobrist = self.zobrist;
+        zobrist ^= V::zobrist_for_white_turn();
+        if let Some(sq) = self.ep_square {
+            zobrist ^= V::zobrist_for_en_passant_file(sq.file());
+        }
+        zobrist
+    }
 
-        let mut hasher = nohash_hasher::NoHashHasher::<Zobrist64>::default();
-        Zobrist64(64).hash(&mut hasher);
-        assert_eq!(hasher.finish(), 64);
+    /// Generates all legal moves, reusing a previously generated list from
+    /// `cache` if the position has been seen before.
+    ///
+    /// The cache is keyed only by the Zobrist hash. In the unlikely event
+    /// of a hash collision, the legal moves of a different position will be
+    /// returned. Use a wider [`ZobristValue`] to make this less likely.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use shakmaty::{Chess, zobrist::{Zobrist, Zobrist64}};
+    ///
+    /// let pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+    /// let mut cache = HashMap::new();
+    /// assert_eq!(pos.legal_moves_cached(&mut cache).len(), 20);
+    /// assert_eq!(cache.len(), 1);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn legal_moves_cached<S>(&self, cache: &mut HashMap<V, MoveList, S>) -> MoveList
+    where
+        V: Hash + Eq,
+        S: BuildHasher,
+    {
+        cache
+            .entry(self.zobrist)
+            .or_insert_with(|| self.pos.legal_moves())
+            .clone()
+    }
 
-        let mut hasher = nohash_hasher::NoHashHasher::<Zobrist32>::default();
-        Zobrist32(32).hash(&mut hasher);
-        assert_eq!(hasher.finish(), 32);
+    /// Plays a move, like [`Position::play_unchecked()`], while also
+    /// reporting each changed square to `state`.
+    ///
+    /// See [`IncrementalState`].
+    pub fn play_unchecked_with<S: IncrementalState + ?Sized>(&mut self, m: &Move, state: &mut S) {
+        debug_assert!(
+            !matches!(*m, Move::Put { .. }) || self.pos.pockets().is_some(),
+            "drops are only valid in variants with pockets"
+        );
 
-        let mut hasher = nohash_hasher::NoHashHasher::<Zobrist16>::default();
-        Zobrist16(16).hash(&mut hasher);
-        assert_eq!(hasher.finish(), 16);
+        let mut zobrist = self.zobrist;
+        let mut pawn_zobrist = self.pawn_zobrist;
+        let mut material_zobrist = self.material_zobrist;
 
-        let mut hasher = nohash_hasher::NoHashHasher::<Zobrist8>::default();
-        Zobrist8(8).hash(&mut hasher);
-        assert_eq!(hasher.finish(), 8);
+        // Remove all components that the move may change. The board is only
+        // a few bitboards, so copying it is cheap.
+        let board = self.pos.board().clone();
+        zobrist ^= hash_promoted::<V>(self.pos.promoted());
+        let pockets = self.pos.pockets().copied();
+        zobrist ^= hash_castles::<V>(self.pos.castles());
+        if let Some(sq) = self.ep_square {
+            zobrist ^= V::zobrist_for_en_passant_file(sq.file());
+        }
+        let remaining_checks = self.pos.remaining_checks().copied();
+
+        self.pos.play_unchecked(m);
+        let after = self.pos.board();
+
+        // Update pieces only on the squares where the placement changed.
+        // This also covers side effects beyond the move itself, like
+        // explosions in atomic chess.
+        let mut changed = (board.occupied() ^ after.occupied()) | (board.white() ^ after.white());
+        for role in Role::ALL {
+            changed |= board.by_role(role) ^ after.by_role(role);
+        }
+        for sq in changed {
+            if let Some(piece) = board.piece_at(sq) {
+                zobrist ^= V::zobrist_for_piece(sq, piece);
+                if piece.role == Role::Pawn {
+                    pawn_zobrist ^= V::zobrist_for_piece(sq, piece);
+                }
+                state.on_remove(sq, piece);
+            }
+        }
+        for sq in changed {
+            if let Some(piece) = after.piece_at(sq) {
+                zobrist ^= V::zobrist_for_piece(sq, piece);
+                if piece.role == Role::Pawn {
+                    pawn_zobrist ^= V::zobrist_for_piece(sq, piece);
+                }
+                state.on_add(sq, piece);
+            }
+        }
+        for color in Color::ALL {
+            for role in Role::ALL {
+                let piece = role.of(color);
+                let (old, new) = (board.by_piece(piece).count(), after.by_piece(piece).count());
+                for n in old.min(new)..old.max(new) {
+                    material_zobrist ^= V::zobrist_for_material(piece, n);
+                }
+            }
+        }
+
+        // Update only the pocket counts that changed, usually a single
+        // increment on capture or decrement on drop.
+        if let (Some(before), Some(after)) = (pockets, self.pos.pockets()) {
+            for color in Color::ALL {
+                for role in Role::ALL {
+                    let (old, new) = (*before.get(color).get(role), *after.get(color).get(role));
+                    if old != new {
+                        zobrist ^= V::zobrist_for_pocket(color, role, old);
+                        zobrist ^= V::zobrist_for_pocket(color, role, new);
+                    }
+                }
+            }
+        }
+
+        // Update only the remaining checks that changed, i.e., those of the
+        // moving side if the move gave check in Three-Check.
+        if let (Some(before), Some(after)) = (remaining_checks, self.pos.remaining_checks()) {
+            for color in Color::ALL {
+                let (old, new) = (*before.get(color), *after.get(color));
+                if old != new {
+                    zobrist ^= V::zobrist_for_remaining_checks(color, old);
+                    zobrist ^= V::zobrist_for_remaining_checks(color, new);
+                }
+            }
+        }
+
+        // Add back the new state.
+        zobrist ^= V::zobrist_for_white_turn();
+        state.on_side_flip();
+        zobrist ^= hash_promoted::<V>(self.pos.promoted());
+        zobrist ^= hash_castles::<V>(self.pos.castles());
+        self.ep_square = self.pos.legal_ep_square();
+        if let Some(sq) = self.ep_square {
+            zobrist ^= V::zobrist_for_en_passant_file(sq.file());
+        }
+
+        self.zobrist = zobrist;
+        self.pawn_zobrist = pawn_zobrist;
+        self.material_zobrist = material_zobrist;
+    }
+}
+
+impl<P, V> Zobrist<P, V> {
+    /// Unwraps the position, discarding the hash.
+    pub fn into_inner(self) -> P {
+        self.pos
+    }
+
+    /// Gets a reference to the wrapped position.
+    pub const fn as_inner(&self) -> &P {
+        &self.pos
+    }
+}
+
+/// Derefs to the wrapped position, for methods that are not part of the
+/// [`Position`] trait. There is intentionally no `DerefMut`, because
+/// changes to the position would not update the hash.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, Position, zobrist::{Zobrist, Zobrist64}};
+///
+/// let pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+/// let inner: &Chess = &pos;
+/// assert_eq!(inner, &Chess::default());
+/// ```
+impl<P, V> Deref for Zobrist<P, V> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        &self.pos
+    }
+}
+
+/// Compares the wrapped positions. Positions with different hashes are
+/// rejected without comparing the positions themselves.
+impl<P: PartialEq, V: PartialEq> PartialEq for Zobrist<P, V> {
+    fn eq(&self, other: &Zobrist<P, V>) -> bool {
+        self.zobrist == other.zobrist && self.pos == other.pos
+    }
+}
+
+impl<P: Eq, V: Eq> Eq for Zobrist<P, V> {}
+
+impl<V: ZobristValue> Zobrist<Chess, V> {
+    /// Plays a move, like [`Position::play_unchecked()`], and returns the
+    /// information needed to take it back with [`Zobrist::unplay()`].
+    ///
+    /// This enables make/unmake style search loops, without recomputing
+    /// any hashes from scratch when going back. Rather than a copy of the
+    /// position, the returned value holds only the move, the castling
+    /// rights, en passant square, move counters, and previous hashes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Position, uci::UciMove, zobrist::{Zobrist, Zobrist64}};
+    ///
+    /// let mut pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+    /// let hash = pos.hash();
+    ///
+    /// let m = "e2e4".parse::<UciMove>()?.to_move(&pos)?;
+    /// let undo = pos.play_and_remember(&m);
+    /// assert_ne!(pos.hash(), hash);
+    ///
+    /// pos.unplay(undo);
+    /// assert_eq!(pos.hash(), hash);
+    ///
+    /// # use shakmaty::uci::{IllegalUciMoveError, ParseUciMoveError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseUciMoveError> for CommonError { fn from(_: ParseUciMoveError) -> Self { Self } }
+    /// # impl From<IllegalUciMoveError> for CommonError { fn from(_: IllegalUciMoveError) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    pub fn play_and_remember(&mut self, m: &Move) -> ZobristUndo<V> {
+        let undo = ZobristUndo {
+            m: *m,
+            state: self.pos.undo_state(),
+            zobrist: self.zobrist,
+            pawn_zobrist: self.pawn_zobrist,
+            material_zobrist: self.material_zobrist,
+            ep_square: self.ep_square,
+        };
+        self.play_unchecked(m);
+        undo
+    }
+
+    /// Takes back the move of [`Zobrist::play_and_remember()`], restoring
+    /// the position and all hashes.
+    ///
+    /// Moves must be taken back in reverse order.
+    pub fn unplay(&mut self, undo: ZobristUndo<V>) {
+        self.pos.unplay_unchecked(&undo.m, undo.state);
+        self.zobrist = undo.zobrist;
+        self.pawn_zobrist = undo.pawn_zobrist;
+        self.material_zobrist = undo.material_zobrist;
+        self.ep_square = undo.ep_square;
+    }
+}
+
+/// Information to take back a move, created by
+/// [`Zobrist::play_and_remember()`].
+#[must_use]
+#[derive(Clone, Debug)]
+pub struct ZobristUndo<V> {
+    m: Move,
+    state: ChessUndo,
+    zobrist: V,
+    pawn_zobrist: V,
+    material_zobrist: V,
+    ep_square: Option<Square>,
+}
+
+/// Formats the hash.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, zobrist::{Zobrist, Zobrist64}};
+///
+/// let pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+/// assert_eq!(format!("{pos:016x}"), "463b96181691fc9c");
+/// ```
+impl<P, V: fmt::LowerHex> fmt::LowerHex for Zobrist<P, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.zobrist, f)
+    }
+}
+
+/// Formats the hash.
+impl<P, V: fmt::UpperHex> fmt::UpperHex for Zobrist<P, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.zobrist, f)
+    }
+}
+
+/// Hashes only the incrementally maintained Zobrist hash, instead of the
+/// entire position.
+///
+/// With the `nohash-hasher` feature, use `nohash_hasher::BuildNoHashHasher`
+/// to avoid hashing it again.
+impl<P, V: Hash> Hash for Zobrist<P, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.zobrist.hash(state);
+    }
+}
+
+#[cfg(feature = "nohash-hasher")]
+impl<P, V: nohash_hasher::IsEnabled> nohash_hasher::IsEnabled for Zobrist<P, V> {}
+
+/// Displays the EPD of the position, followed by the hash.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, zobrist::{Zobrist, Zobrist64}};
+///
+/// let pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+/// assert_eq!(
+///     pos.to_string(),
+///     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 463b96181691fc9c"
+/// );
+/// ```
+impl<P: Position + Clone, V: fmt::LowerHex> fmt::Display for Zobrist<P, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let epd = Epd::from_position(self.pos.clone(), EnPassantMode::Legal);
+        write!(f, "{epd} {:x}", self.zobrist)
+    }
+}
+
+impl<P: Position + Default, V: ZobristValue> Default for Zobrist<P, V> {
+    fn default() -> Zobrist<P, V> {
+        Zobrist::new(P::default())
+    }
+}
+
+/// Sets up a position and computes its hash from scratch.
+///
+/// If setting up the position fails, but the error is recoverable with one
+/// of the `PositionError::ignore_*()` methods, the recovered position carries
+/// the correct hash. Otherwise, the hash in the error is
+/// [`Default::default()`] and must not be relied upon.
+impl<P: Position + FromSetup, V: ZobristValue> FromSetup for Zobrist<P, V> {
+    fn from_setup(setup: Setup, mode: CastlingMode) -> Result<Self, PositionError<Self>> {
+        match P::from_setup(setup, mode) {
+            Ok(pos) => Ok(Zobrist::new(pos)),
+            Err(PositionError { pos, errors }) => {
+                // The position can only be recovered from the error if all
+                // remaining errors can be ignored. Otherwise it may not even
+                // be safe to compute the hash (for example, if kings are
+                // missing).
+                let recoverable = PositionErrorKinds::INVALID_CASTLING_RIGHTS
+                    | PositionErrorKinds::INVALID_EP_SQUARE
+                    | PositionErrorKinds::TOO_MUCH_MATERIAL
+                    | PositionErrorKinds::IMPOSSIBLE_CHECK;
+                let pos = if recoverable.contains(errors) {
+                    Zobrist::new(pos)
+                } else {
+                    Zobrist {
+                        pos,
+                        zobrist: V::default(),
+                        pawn_zobrist: V::default(),
+                        material_zobrist: V::default(),
+                        ep_square: None,
+                    }
+                };
+                Err(PositionError { pos, errors })
+            }
+        }
+    }
+}
+
+impl<P: Position + FromSetup, V: ZobristValue + PartialEq> Zobrist<P, V> {
+    /// Sets up a position, like [`FromSetup::from_setup()`], and validates
+    /// its hash against a previously stored `expected` hash.
+    ///
+    /// This is useful when loading persisted pairs of positions and hashes,
+    /// for example from an opening book or a transposition table dump.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ZobristSetupError::Position`] if the setup is not a legal
+    /// position, or [`ZobristSetupError::HashMismatch`] if its hash differs
+    /// from `expected`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{CastlingMode, Chess, Setup, zobrist::{Zobrist, Zobrist64}};
+    ///
+    /// let pos: Zobrist<Chess, Zobrist64> = Zobrist::from_setup_checked(
+    ///     Setup::initial(),
+    ///     CastlingMode::Standard,
+    ///     Zobrist64(0x463b96181691fc9c),
+    /// )?;
+    /// # Ok::<_, shakmaty::zobrist::ZobristSetupError<_, _>>(())
+    /// ```
+    pub fn from_setup_checked(
+        setup: Setup,
+        mode: CastlingMode,
+        expected: V,
+    ) -> Result<Zobrist<P, V>, ZobristSetupError<P, V>> {
+        let pos = Zobrist::from_setup(setup, mode).map_err(ZobristSetupError::Position)?;
+        if pos.zobrist == expected {
+            Ok(pos)
+        } else {
+            Err(ZobristSetupError::HashMismatch { pos, expected })
+        }
+    }
+}
+
+/// Error when setting up a position with [`Zobrist::from_setup_checked()`].
+pub enum ZobristSetupError<P, V> {
+    /// The setup is not a legal position.
+    Position(PositionError<Zobrist<P, V>>),
+    /// The position is legal, but its hash does not match the expected hash.
+    HashMismatch {
+        /// The position, with its actual hash.
+        pos: Zobrist<P, V>,
+        /// The expected hash.
+        expected: V,
+    },
+}
+
+impl<P, V: fmt::Debug> fmt::Debug for ZobristSetupError<P, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZobristSetupError::Position(err) => f.debug_tuple("Position").field(err).finish(),
+            ZobristSetupError::HashMismatch { pos, expected } => f
+                .debug_struct("HashMismatch")
+                .field("actual", &pos.zobrist)
+                .field("expected", expected)
+                .finish(),
+        }
+    }
+}
+
+impl<P, V: fmt::LowerHex> fmt::Display for ZobristSetupError<P, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZobristSetupError::Position(err) => fmt::Display::fmt(err, f),
+            ZobristSetupError::HashMismatch { pos, expected } => write!(
+                f,
+                "zobrist hash mismatch: expected {expected:x}, got {:x}",
+                pos.zobrist
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<P, V: fmt::Debug + fmt::LowerHex> std::error::Error for ZobristSetupError<P, V> {}
+
+impl<P: Position, V: ZobristValue> Position for Zobrist<P, V> {
+    fn board(&self) -> &Board {
+        self.pos.board()
+    }
+
+    fn promoted(&self) -> Bitboard {
+        self.pos.promoted()
+    }
+
+    fn pockets(&self) -> Option<&ByColor<ByRole<u8>>> {
+        self.pos.pockets()
+    }
+
+    fn turn(&self) -> Color {
+        self.pos.turn()
+    }
+
+    fn castles(&self) -> &Castles {
+        self.pos.castles()
+    }
+
+    fn maybe_ep_square(&self) -> Option<Square> {
+        self.pos.maybe_ep_square()
+    }
+
+    fn remaining_checks(&self) -> Option<&ByColor<RemainingChecks>> {
+        self.pos.remaining_checks()
+    }
+
+    fn halfmoves(&self) -> u32 {
+        self.pos.halfmoves()
+    }
+
+    fn fullmoves(&self) -> NonZeroU32 {
+        self.pos.fullmoves()
+    }
+
+    fn into_setup(self, mode: EnPassantMode) -> Setup {
+        self.pos.into_setup(mode)
+    }
+
+    fn legal_moves(&self) -> MoveList {
+        self.pos.legal_moves()
+    }
+
+    fn san_candidates(&self, role: Role, to: Square) -> MoveList {
+        self.pos.san_candidates(role, to)
+    }
+
+    fn castling_moves(&self, side: CastlingSide) -> MoveList {
+        self.pos.castling_moves(side)
+    }
+
+    fn en_passant_moves(&self) -> MoveList {
+        self.pos.en_passant_moves()
+    }
+
+    fn capture_moves(&self) -> MoveList {
+        self.pos.capture_moves()
+    }
+
+    fn promotion_moves(&self) -> MoveList {
+        self.pos.promotion_moves()
+    }
+
+    fn is_irreversible(&self, m: &Move) -> bool {
+        self.pos.is_irreversible(m)
+    }
+
+    fn king_attackers(&self, square: Square, attacker: Color, occupied: Bitboard) -> Bitboard {
+        self.pos.king_attackers(square, attacker, occupied)
+    }
+
+    fn is_variant_end(&self) -> bool {
+        self.pos.is_variant_end()
+    }
+
+    fn has_insufficient_material(&self, color: Color) -> bool {
+        self.pos.has_insufficient_material(color)
+    }
+
+    fn variant_outcome(&self) -> Option<Outcome> {
+        self.pos.variant_outcome()
+    }
+
+    fn play_unchecked(&mut self, m: &Move) {
+        self.play_unchecked_with(m, &mut ());
+    }
+}
+
+impl<V: ZobristValue> Zobrist<Chess, V> {
+    /// Computes the hash of the position after playing the legal move `m`,
+    /// without playing it, for example to prefetch transposition table
+    /// entries.
+    ///
+    /// Only double pawn pushes that allow a pseudo-legal en passant
+    /// capture need to clone the position, to determine if the en passant
+    /// square is legal and therefore part of the hash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Position, uci::UciMove, zobrist::{Zobrist, Zobrist64}};
+    ///
+    /// let mut pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+    /// let m = "e2e4".parse::<UciMove>()?.to_move(&pos)?;
+    /// let hash = pos.hash_after(&m);
+    /// pos.play_unchecked(&m);
+    /// assert_eq!(pos.hash(), hash);
+    ///
+    /// # use shakmaty::uci::{IllegalUciMoveError, ParseUciMoveError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseUciMoveError> for CommonError { fn from(_: ParseUciMoveError) -> Self { Self } }
+    /// # impl From<IllegalUciMoveError> for CommonError { fn from(_: IllegalUciMoveError) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    pub fn hash_after(&self, m: &Move) -> V {
+        let board = self.pos.board();
+        let us = self.pos.turn();
+        let mut castles = self.pos.castles().clone();
+
+        let mut zobrist = self.zobrist;
+        zobrist ^= V::zobrist_for_white_turn();
+        if let Some(sq) = self.ep_square {
+            zobrist ^= V::zobrist_for_en_passant_file(sq.file());
+        }
+
+        match *m {
+            Move::Normal {
+                role,
+                from,
+                capture,
+                to,
+                promotion,
+            } => {
+                if role == Role::Pawn && from.rank().distance(to.rank()) == 2 {
+                    let ep_square = Square::new((u32::from(from) + u32::from(to)) / 2);
+                    if (attacks::pawn_attacks(us, ep_square) & board.pawns() & board.by_color(!us))
+                        .any()
+                    {
+                        let mut child = self.clone();
+                        child.play_unchecked(m);
+                        return child.hash();
+                    }
+                }
+
+                zobrist ^= V::zobrist_for_piece(from, role.of(us));
+                if let Some(capture) = capture {
+                    zobrist ^= V::zobrist_for_piece(to, capture.of(!us));
+                }
+                zobrist ^= V::zobrist_for_piece(to, promotion.unwrap_or(role).of(us));
+
+                if role == Role::King {
+                    castles.discard_color(us);
+                } else if role == Role::Rook {
+                    castles.discard_rook(from);
+                }
+                if capture == Some(Role::Rook) {
+                    castles.discard_rook(to);
+                }
+            }
+            Move::Castle { king, rook } => {
+                let side = CastlingSide::from_queen_side(rook < king);
+                zobrist ^= V::zobrist_for_piece(king, us.king());
+                zobrist ^= V::zobrist_for_piece(rook, us.rook());
+                zobrist ^= V::zobrist_for_piece(side.king_to(us), us.king());
+                zobrist ^= V::zobrist_for_piece(side.rook_to(us), us.rook());
+                castles.discard_color(us);
+            }
+            Move::EnPassant { from, to } => {
+                zobrist ^= V::zobrist_for_piece(from, us.pawn());
+                zobrist ^= V::zobrist_for_piece(to, us.pawn());
+                zobrist ^=
+                    V::zobrist_for_piece(Square::from_coords(to.file(), from.rank()), (!us).pawn());
+            }
+            Move::Put { role, to } => {
+                zobrist ^= V::zobrist_for_piece(to, role.of(us));
+            }
+        }
+
+        zobrist ^= hash_castles::<V>(self.pos.castles());
+        zobrist ^= hash_castles::<V>(&castles);
+        zobrist
+    }
+}
+
+/// History of position hashes in a game, to detect repetitions.
+///
+/// Push the hash of every position in the game, including the current one.
+/// Alternatively, play moves with [`Zobrist::play_tracked()`], which keeps
+/// only the positions since the last irreversible move.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, EnPassantMode, Position, zobrist::{RepetitionTable, ZobristHash}};
+///
+/// let pos = Chess::default();
+/// let mut table = RepetitionTable::new();
+/// table.push(pos.zobrist_hash(EnPassantMode::Legal));
+/// assert_eq!(table.count(pos.zobrist_hash(EnPassantMode::Legal)), 1);
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default)]
+pub struct RepetitionTable<V = Zobrist64> {
+    hashes: alloc::vec::Vec<V>,
+}
+
+#[cfg(feature = "alloc")]
+impl RepetitionTable {
+    /// Creates an empty table of [`Zobrist64`] hashes. Use
+    /// [`RepetitionTable::default()`] for other hash types.
+    pub const fn new() -> RepetitionTable {
+        RepetitionTable {
+            hashes: alloc::vec::Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V> RepetitionTable<V> {
+    /// Records the hash of a position.
+    pub fn push(&mut self, hash: V) {
+        self.hashes.push(hash);
+    }
+
+    /// Removes the most recently recorded hash, for example when taking
+    /// back a move.
+    pub fn pop(&mut self) -> Option<V> {
+        self.hashes.pop()
+    }
+
+    /// Forgets all recorded hashes, for example after an irreversible move,
+    /// and records the hash of the new position.
+    pub fn reset(&mut self, hash: V) {
+        self.hashes.clear();
+        self.hashes.push(hash);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<V: PartialEq> RepetitionTable<V> {
+    /// Counts how often a position with the given hash was recorded.
+    pub fn count(&self, hash: V) -> usize {
+        self.hashes.iter().filter(|h| **h == hash).count()
+    }
+
+    /// Counts how often the most recently recorded position was recorded.
+    fn count_last(&self) -> usize {
+        self.hashes
+            .last()
+            .map_or(0, |last| self.hashes.iter().filter(|h| *h == last).count())
+    }
+
+    /// Tests if the most recently recorded position was recorded at least
+    /// three times.
+    pub fn is_threefold(&self) -> bool {
+        self.count_last() >= 3
+    }
+
+    /// Tests if the most recently recorded position was recorded at least
+    /// five times.
+    pub fn is_fivefold(&self) -> bool {
+        self.count_last() >= 5
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Position, V: ZobristValue> Zobrist<P, V> {
+    /// Plays a move like [`Position::play_unchecked()`] and records the
+    /// resulting position in `table`. After irreversible moves, the table is
+    /// [reset](RepetitionTable::reset()), because earlier positions can no
+    /// longer be repeated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, uci::UciMove, zobrist::{RepetitionTable, Zobrist, Zobrist64}};
+    ///
+    /// let mut pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+    /// let mut table = RepetitionTable::new();
+    /// table.push(pos.hash());
+    ///
+    /// for _ in 0..2 {
+    ///     for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+    ///         let m = uci.parse::<UciMove>()?.to_move(&pos)?;
+    ///         pos.play_tracked(&m, &mut table);
+    ///     }
+    /// }
+    ///
+    /// assert!(table.is_threefold());
+    /// assert!(!table.is_fivefold());
+    ///
+    /// # use shakmaty::uci::{IllegalUciMoveError, ParseUciMoveError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseUciMoveError> for CommonError { fn from(_: ParseUciMoveError) -> Self { Self } }
+    /// # impl From<IllegalUciMoveError> for CommonError { fn from(_: IllegalUciMoveError) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    pub fn play_tracked(&mut self, m: &Move, table: &mut RepetitionTable<V>) {
+        let irreversible = self.is_irreversible(m);
+        self.play_unchecked(m);
+        if irreversible {
+            table.reset(self.zobrist);
+        } else {
+            table.push(self.zobrist);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<P: Position + Clone> Zobrist<P, Zobrist64> {
+    /// Tests if playing the legal move `m` would lead to a position that is
+    /// already recorded in `table`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, Position, uci::UciMove, zobrist::{RepetitionTable, Zobrist, Zobrist64}};
+    ///
+    /// let mut pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+    /// let mut table = RepetitionTable::new();
+    /// table.push(pos.repetition_key());
+    ///
+    /// for uci in ["g1f3", "g8f6", "f3g1"] {
+    ///     let m = uci.parse::<UciMove>()?.to_move(&pos)?;
+    ///     pos.play_unchecked(&m);
+    ///     table.push(pos.repetition_key());
+    /// }
+    ///
+    /// let ng8 = "f6g8".parse::<UciMove>()?.to_move(&pos)?;
+    /// assert!(pos.repeats_after(&ng8, &table));
+    ///
+    /// # use shakmaty::uci::{IllegalUciMoveError, ParseUciMoveError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseUciMoveError> for CommonError { fn from(_: ParseUciMoveError) -> Self { Self } }
+    /// # impl From<IllegalUciMoveError> for CommonError { fn from(_: IllegalUciMoveError) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    pub fn repeats_after(&self, m: &Move, table: &RepetitionTable) -> bool {
+        let mut pos = self.clone();
+        pos.play_unchecked(m);
+        table.count(pos.repetition_key()) > 0
+    }
+}
+
+/// Keys and moves of all reversible piece moves on an empty board, in a
+/// cuckoo hash table, as used by [`Zobrist::has_game_cycle()`].
+#[cfg(feature = "std")]
+struct Cuckoo {
+    keys: alloc::vec::Vec<Zobrist64>,
+    moves: alloc::vec::Vec<Option<(Square, Square)>>,
+}
+
+#[cfg(feature = "std")]
+impl Cuckoo {
+    const SIZE: usize = 0x2000;
+
+    fn h1(key: Zobrist64) -> usize {
+        (key.0 & 0x1fff) as usize
+    }
+
+    fn h2(key: Zobrist64) -> usize {
+        ((key.0 >> 16) & 0x1fff) as usize
+    }
+
+    fn new() -> Cuckoo {
+        let mut cuckoo = Cuckoo {
+            keys: alloc::vec![Zobrist64::default(); Cuckoo::SIZE],
+            moves: alloc::vec![None; Cuckoo::SIZE],
+        };
+        for color in Color::ALL {
+            for role in [
+                Role::Knight,
+                Role::Bishop,
+                Role::Rook,
+                Role::Queen,
+                Role::King,
+            ] {
+                let piece = role.of(color);
+                for a in Square::ALL {
+                    for b in attacks::attacks(a, piece, Bitboard::EMPTY) {
+                        if a < b {
+                            cuckoo.insert(
+                                Zobrist64::zobrist_for_piece(a, piece)
+                                    ^ Zobrist64::zobrist_for_piece(b, piece)
+                                    ^ Zobrist64::zobrist_for_white_turn(),
+                                (a, b),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        cuckoo
+    }
+
+    fn insert(&mut self, mut key: Zobrist64, m: (Square, Square)) {
+        let mut m = Some(m);
+        let mut i = Cuckoo::h1(key);
+        loop {
+            core::mem::swap(&mut self.keys[i], &mut key);
+            core::mem::swap(&mut self.moves[i], &mut m);
+            if m.is_none() {
+                break;
+            }
+            // Push the evicted entry to its alternative slot.
+            i = if i == Cuckoo::h1(key) {
+                Cuckoo::h2(key)
+            } else {
+                Cuckoo::h1(key)
+            };
+        }
+    }
+
+    fn get(&self, key: Zobrist64) -> Option<(Square, Square)> {
+        [Cuckoo::h1(key), Cuckoo::h2(key)]
+            .into_iter()
+            .find(|&i| self.keys[i] == key)
+            .and_then(|i| self.moves[i])
+    }
+
+    fn shared() -> &'static Cuckoo {
+        static CUCKOO: std::sync::OnceLock<Cuckoo> = std::sync::OnceLock::new();
+        CUCKOO.get_or_init(Cuckoo::new)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Zobrist<Chess, Zobrist64> {
+    /// Tests if the side to move has a reversible move that reaches a
+    /// position of the game history, or if such a move was just played
+    /// within the search, so that a search can score the position as a draw
+    /// before the repetition actually occurs.
+    ///
+    /// `history` contains the positions of the game, ending with the current
+    /// one, as recorded by [`Zobrist::play_tracked()`]. `ply` is the
+    /// distance of the current position from the root of the search. Cycles
+    /// that reach back to the root or beyond are only reported if they
+    /// return to a position that already occurred twice.
+    ///
+    /// Uses a cuckoo hash table of reversible piece moves, so that only the
+    /// hashes of the history need to be inspected (Marcel van Kervinck's
+    /// method, as used in Stockfish). The table is built once, on first use.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shakmaty::{Chess, uci::UciMove, zobrist::{RepetitionTable, Zobrist, Zobrist64}};
+    ///
+    /// let mut pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+    /// let mut history = RepetitionTable::new();
+    /// history.push(pos.hash());
+    /// for uci in ["g1f3", "g8f6", "f3g1"] {
+    ///     let m = uci.parse::<UciMove>()?.to_move(&pos)?;
+    ///     pos.play_tracked(&m, &mut history);
+    /// }
+    ///
+    /// // Deep within a search, Black can play Ng8 to return to the starting
+    /// // position.
+    /// assert!(pos.has_game_cycle(&history, 10));
+    ///
+    /// // But at the root, the starting position occurred only once.
+    /// assert!(!pos.has_game_cycle(&history, 0));
+    ///
+    /// # use shakmaty::uci::{IllegalUciMoveError, ParseUciMoveError};
+    /// # #[derive(Debug)] struct CommonError;
+    /// # impl From<ParseUciMoveError> for CommonError { fn from(_: ParseUciMoveError) -> Self { Self } }
+    /// # impl From<IllegalUciMoveError> for CommonError { fn from(_: IllegalUciMoveError) -> Self { Self } }
+    /// # Ok::<_, CommonError>(())
+    /// ```
+    pub fn has_game_cycle(&self, history: &RepetitionTable, ply: usize) -> bool {
+        let Some((&current, history)) = history.hashes.split_last() else {
+            return false;
+        };
+        debug_assert_eq!(current, self.zobrist, "history ends with current position");
+
+        let end = history.len().min(self.halfmoves() as usize);
+        if end < 3 {
+            return false;
+        }
+
+        // The hash of the position `n` plies ago.
+        let key = |n: usize| {
+            if n == 0 {
+                self.zobrist
+            } else {
+                history[history.len() - n]
+            }
+        };
+
+        let cuckoo = Cuckoo::shared();
+        let mut other = key(0) ^ key(1) ^ Zobrist64::zobrist_for_white_turn();
+        for i in (3..=end).step_by(2) {
+            other ^= key(i - 1) ^ key(i) ^ Zobrist64::zobrist_for_white_turn();
+            if other != Zobrist64::default() {
+                continue;
+            }
+
+            let Some((a, b)) = cuckoo.get(key(0) ^ key(i)) else {
+                continue;
+            };
+            if attacks::between(a, b).intersects(self.board().occupied()) {
+                continue;
+            }
+            if ply > i {
+                return true;
+            }
+            // Before the root, the move must be a repetition for the side
+            // to move, of a position that already repeated.
+            let piece_color = self.board().color_at(a).or(self.board().color_at(b));
+            if piece_color == Some(self.turn()) && history[..history.len() - i].contains(&key(i)) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Replacement policy of a [`TranspositionTable`], deciding whether a new
+/// entry overwrites an occupied slot.
+#[cfg(feature = "alloc")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Replacement {
+    /// Always overwrite the previous entry.
+    #[default]
+    Always,
+    /// Overwrite the previous entry only if it has the same key, or if the
+    /// new entry was searched at least as deep.
+    PreferDepth,
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+struct TranspositionEntry<T> {
+    key: Zobrist64,
+    depth: u32,
+    value: T,
+}
+
+/// A fixed-size hash table of search results, indexed by [`Zobrist64`]
+/// hashes.
+///
+/// Each hash maps to exactly one slot. When two positions compete for the
+/// same slot, the [`Replacement`] policy decides which one is kept. The full
+/// hash is stored along with each entry, so that lookups never return
+/// entries of other positions (barring hash collisions).
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, zobrist::{TranspositionTable, Zobrist, Zobrist64}};
+///
+/// let pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+/// let mut tt = TranspositionTable::new(1024);
+/// tt.insert(pos.hash(), 3, 8902);
+/// assert_eq!(tt.get(pos.hash()), Some(&8902));
+/// assert_eq!(tt.depth(pos.hash()), Some(3));
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone)]
+pub struct TranspositionTable<T> {
+    entries: alloc::vec::Vec<Option<TranspositionEntry<T>>>,
+    replacement: Replacement,
+}
+
+#[cfg(feature = "alloc")]
+impl<T> TranspositionTable<T> {
+    /// Creates an empty table with `size` slots, that always replaces
+    /// previous entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is `0`.
+    pub fn new(size: usize) -> TranspositionTable<T> {
+        TranspositionTable::with_replacement(size, Replacement::Always)
+    }
+
+    /// Creates an empty table with `size` slots and the given replacement
+    /// policy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is `0`.
+    pub fn with_replacement(size: usize, replacement: Replacement) -> TranspositionTable<T> {
+        assert!(size > 0, "transposition table needs at least one slot");
+        let mut entries = alloc::vec::Vec::with_capacity(size);
+        entries.resize_with(size, || None);
+        TranspositionTable {
+            entries,
+            replacement,
+        }
+    }
+
+    /// Returns the number of slots.
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the replacement policy.
+    pub fn replacement(&self) -> Replacement {
+        self.replacement
+    }
+
+    fn index(&self, key: Zobrist64) -> usize {
+        tt_index(key, self.entries.len())
+    }
+
+    fn entry(&self, key: Zobrist64) -> Option<&TranspositionEntry<T>> {
+        self.entries[self.index(key)]
+            .as_ref()
+            .filter(|entry| entry.key == key)
+    }
+
+    /// Gets the value stored for the position with the given hash.
+    pub fn get(&self, key: Zobrist64) -> Option<&T> {
+        self.entry(key).map(|entry| &entry.value)
+    }
+
+    /// Gets the depth of the value stored for the position with the given
+    /// hash.
+    pub fn depth(&self, key: Zobrist64) -> Option<u32> {
+        self.entry(key).map(|entry| entry.depth)
+    }
+
+    /// Stores a value for the position with the given hash, subject to the
+    /// replacement policy. Returns `true` if the value was stored.
+    pub fn insert(&mut self, key: Zobrist64, depth: u32, value: T) -> bool {
+        let replacement = self.replacement;
+        let index = self.index(key);
+        let slot = &mut self.entries[index];
+        let replace = match (replacement, &slot) {
+            (Replacement::Always, _) | (Replacement::PreferDepth, None) => true,
+            (Replacement::PreferDepth, Some(old)) => old.key == key || depth >= old.depth,
+        };
+        if replace {
+            *slot = Some(TranspositionEntry { key, depth, value });
+        }
+        replace
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.entries.fill_with(|| None);
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn tt_index(key: Zobrist64, size: usize) -> usize {
+    // Maps the hash uniformly to 0..size, without requiring a power of two.
+    ((u128::from(key.0) * size as u128) >> 64) as usize
+}
+
+/// A fixed-size table of 64 bit values, indexed by [`Zobrist64`] hashes, that
+/// can be shared between threads without locking.
+///
+/// Each slot stores the value along with the hash XOR the value. Concurrent
+/// writes to the same slot can tear, but a torn entry no longer matches its
+/// hash and is treated as a miss. New entries always replace previous ones.
+///
+/// Empty slots are all zero, so the value `0` for the hash
+/// `Zobrist64(0)` cannot be told apart from a missing entry, and is never
+/// found by [`LocklessTable::load()`]. Any other value for that hash, and
+/// any value for other hashes, round-trips.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, zobrist::{LocklessTable, Zobrist, Zobrist64}};
+///
+/// let pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+/// let tt = LocklessTable::new(1024);
+/// tt.store(pos.hash(), 20);
+/// assert_eq!(tt.load(pos.hash()), Some(20));
+/// ```
+#[cfg(all(feature = "alloc", target_has_atomic = "64"))]
+#[derive(Debug)]
+pub struct LocklessTable {
+    entries: alloc::vec::Vec<(AtomicU64, AtomicU64)>,
+}
+
+#[cfg(all(feature = "alloc", target_has_atomic = "64"))]
+impl LocklessTable {
+    /// Creates an empty table with `size` slots.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is `0`.
+    pub fn new(size: usize) -> LocklessTable {
+        assert!(size > 0, "transposition table needs at least one slot");
+        let mut entries = alloc::vec::Vec::with_capacity(size);
+        // Empty slots are all zeros, which is never reported as a hit.
+        entries.resize_with(size, || (AtomicU64::new(0), AtomicU64::new(0)));
+        LocklessTable { entries }
+    }
+
+    /// Returns the number of slots.
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Gets the value stored for the position with the given hash.
+    ///
+    /// Returns `None` for the value `0` stored with the hash `Zobrist64(0)`,
+    /// which is indistinguishable from an empty slot.
+    pub fn load(&self, key: Zobrist64) -> Option<u64> {
+        let (check, data) = &self.entries[tt_index(key, self.entries.len())];
+        let check = check.load(Ordering::Relaxed);
+        let data = data.load(Ordering::Relaxed);
+        (check ^ data == key.0 && (check, data) != (0, 0)).then_some(data)
+    }
+
+    /// Stores a value for the position with the given hash.
+    pub fn store(&self, key: Zobrist64, value: u64) {
+        let (check, data) = &self.entries[tt_index(key, self.entries.len())];
+        check.store(key.0 ^ value, Ordering::Relaxed);
+        data.store(value, Ordering::Relaxed);
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        for (check, data) in &mut self.entries {
+            *check.get_mut() = 0;
+            *data.get_mut() = 0;
+        }
+    }
+}
+
+/// Searches for a line of at most `max_depth` moves from `start` that leads
+/// to a position with the hash `target`.
+///
+/// Shorter lines are preferred. This is a brute force search, intended for
+/// debugging, for example to reproduce reported hash collisions. The number
+/// of explored positions grows exponentially with `max_depth`.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, zobrist::{find_line_to_hash, Zobrist, Zobrist64}};
+///
+/// let start: Zobrist<Chess, Zobrist64> = Zobrist::default();
+/// let line = find_line_to_hash(&start, Zobrist64(0x823c9b50fd114196), 2).expect("found");
+/// assert_eq!(line.len(), 1);
+/// assert_eq!(line[0].to_string(), "e2-e4");
+/// ```
+#[cfg(feature = "alloc")]
+pub fn find_line_to_hash<P, V>(
+    start: &Zobrist<P, V>,
+    target: V,
+    max_depth: u32,
+) -> Option<alloc::vec::Vec<Move>>
+where
+    P: Position + Clone,
+    V: ZobristValue + PartialEq,
+{
+    fn search<P, V>(
+        pos: &Zobrist<P, V>,
+        target: V,
+        depth: u32,
+        line: &mut alloc::vec::Vec<Move>,
+    ) -> bool
+    where
+        P: Position + Clone,
+        V: ZobristValue + PartialEq,
+    {
+        if depth == 0 {
+            return pos.hash() == target;
+        }
+        for m in pos.legal_moves() {
+            let mut child = pos.clone();
+            child.play_unchecked(&m);
+            line.push(m);
+            if search(&child, target, depth - 1, line) {
+                return true;
+            }
+            line.pop();
+        }
+        false
+    }
+
+    let mut line = alloc::vec::Vec::new();
+    (0..=max_depth)
+        .any(|depth| search(start, target, depth, &mut line))
+        .then_some(line)
+}
+
+/// Plays a line of moves in Standard Algebraic Notation from `start` and
+/// returns the hash of the final position.
+///
+/// # Errors
+///
+/// Returns [`SanError::IllegalSan`] if any of the moves is not
+/// syntactically valid or does not match a legal move, and
+/// [`SanError::AmbiguousSan`] if it matches multiple legal moves.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, zobrist::{hash_after_san_line, Zobrist, Zobrist64}};
+///
+/// let start: Zobrist<Chess, Zobrist64> = Zobrist::default();
+/// let hash = hash_after_san_line(&start, &["e4", "d5", "e5", "f5"])?;
+/// assert_eq!(hash, Zobrist64(0x22a48b5a8e47ff78));
+/// # Ok::<_, shakmaty::san::SanError>(())
+/// ```
+pub fn hash_after_san_line<P, V>(start: &Zobrist<P, V>, sans: &[&str]) -> Result<V, SanError>
+where
+    P: Position + Clone,
+    V: ZobristValue,
+{
+    let mut pos = start.clone();
+    for san in sans {
+        let m = San::from_ascii(san.as_bytes())
+            .map_err(|_| SanError::IllegalSan)?
+            .to_move(&pos)?;
+        pos.play_unchecked(&m);
+    }
+    Ok(pos.hash())
+}
+
+/// Computes an order dependent fingerprint of a game, for example to
+/// deduplicate games in a database.
+///
+/// The hashes of the starting position and of the position after each move
+/// are combined with a rolling hash: starting with `0`, each position hash
+/// `h` updates the fingerprint `f` to `f * 0x9e37_79b9_7f4a_7c15 + h`
+/// (wrapping). Identical games have identical fingerprints, while games
+/// reaching the same final position by different move orders almost
+/// certainly differ.
+///
+/// The `moves` must be legal.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{Chess, Position, uci::UciMove, zobrist::{game_fingerprint, Zobrist, Zobrist64}};
+///
+/// let start: Zobrist<Chess, Zobrist64> = Zobrist::default();
+/// let mut moves = Vec::new();
+/// let mut pos = start.clone();
+/// for uci in ["e2e4", "e7e5"] {
+///     let m = uci.parse::<UciMove>()?.to_move(&pos)?;
+///     pos.play_unchecked(&m);
+///     moves.push(m);
+/// }
+///
+/// assert_eq!(game_fingerprint(&start, &moves), game_fingerprint(&start, &moves));
+/// assert_ne!(game_fingerprint(&start, &moves), game_fingerprint(&start, &moves[..1]));
+///
+/// # use shakmaty::uci::{IllegalUciMoveError, ParseUciMoveError};
+/// # #[derive(Debug)] struct CommonError;
+/// # impl From<ParseUciMoveError> for CommonError { fn from(_: ParseUciMoveError) -> Self { Self } }
+/// # impl From<IllegalUciMoveError> for CommonError { fn from(_: IllegalUciMoveError) -> Self { Self } }
+/// # Ok::<_, CommonError>(())
+/// ```
+pub fn game_fingerprint<P>(start: &Zobrist<P, Zobrist64>, moves: &[Move]) -> Zobrist64
+where
+    P: Position + Clone,
+{
+    let combine = |fingerprint: u64, hash: Zobrist64| {
+        fingerprint
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15)
+            .wrapping_add(hash.0)
+    };
+
+    let mut pos = start.clone();
+    let mut fingerprint = combine(0, pos.hash());
+    for m in moves {
+        pos.play_unchecked(m);
+        fingerprint = combine(fingerprint, pos.hash());
+    }
+    Zobrist64(fingerprint)
+}
+
+/// Deterministic linear congruential generator, to pick moves in random
+/// games without additional dependencies.
+#[cfg(any(test, feature = "verify"))]
+struct RandomMoves(u64);
+
+#[cfg(any(test, feature = "verify"))]
+impl RandomMoves {
+    const fn new(seed: u64) -> RandomMoves {
+        RandomMoves(seed)
+    }
+
+    /// Picks a number in `0..n`.
+    fn next(&mut self, n: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (self.0 >> 33) as usize % n
+    }
+
+    /// Picks a legal move, or `None` if the game is over. With
+    /// `prefer_captures`, captures (including en passant) are picked three
+    /// times out of four, if there are any.
+    fn pick<P: Position>(&mut self, pos: &P, prefer_captures: bool) -> Option<Move> {
+        let captures = if prefer_captures {
+            pos.capture_moves()
+        } else {
+            MoveList::new()
+        };
+        let moves = if captures.is_empty() || self.next(4) == 0 {
+            pos.legal_moves()
+        } else {
+            captures
+        };
+        (!moves.is_empty()).then(|| moves[self.next(moves.len())])
+    }
+}
+
+/// Plays `games` random games from the default position of `P` and asserts
+/// that the incrementally updated hashes of [`Zobrist`] (including the
+/// [pawn hash](Zobrist::pawn_hash()) and the
+/// [material hash](Zobrist::material_hash())) match the hashes computed from scratch
+/// after every move.
+///
+/// Moves are picked by a deterministic generator seeded with `seed`, so
+/// failures are reproducible. Captures are preferred, to reach positions
+/// with promotions, en passant, and variant specific side effects. Games
+/// are limited to 200 plies.
+///
+/// When adding a new variant, call this from its tests to check that its
+/// [`Position::play_unchecked()`] keeps the hash consistent, for example
+/// with a few different seeds.
+///
+/// # Panics
+///
+/// Panics with the FEN of the position and the offending move if the
+/// hashes ever disagree.
+///
+/// # Examples
+///
+/// ```
+/// use shakmaty::{zobrist::verify_incremental, Chess};
+///
+/// verify_incremental::<Chess>(4, 42);
+/// ```
+#[cfg(feature = "verify")]
+pub fn verify_incremental<P>(games: usize, seed: u64)
+where
+    P: Position + Default + Clone,
+{
+    use crate::fen::Fen;
+
+    let mut rng = RandomMoves::new(seed);
+    for _ in 0..games {
+        let mut pos: Zobrist<P, Zobrist64> = Zobrist::default();
+        for _ in 0..200 {
+            let Some(m) = rng.pick(&pos, true) else {
+                break;
+            };
+
+            let fen = Fen::from_position(pos.clone().into_inner(), EnPassantMode::Legal);
+            pos.play_unchecked(&m);
+
+            let expected = pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal);
+            assert_eq!(pos.hash(), expected, "incremental hash after {m} in {fen}");
+            let expected = pos.region_hash(pos.board().pawns());
+            assert_eq!(pos.pawn_hash(), expected, "pawn hash after {m} in {fen}");
+            let expected = hash_material(pos.board());
+            assert_eq!(
+                pos.material_hash(),
+                expected,
+                "material hash after {m} in {fen}"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fen::Fen, CastlingMode, Chess};
+
+    #[test]
+    fn test_polyglot() {
+        let reference_values = [
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                Zobrist64(0x463b96181691fc9c),
+            ),
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+                Zobrist64(0x823c9b50fd114196),
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2",
+                Zobrist64(0x0756b94461c50fb0),
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+                Zobrist64(0x662fafb965db29d4),
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                Zobrist64(0x22a48b5a8e47ff78),
+            ),
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 1 3",
+                Zobrist64(0x652a607ca3f242c1),
+            ),
+            (
+                "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 2 4",
+                Zobrist64(0x00fdd303c946bdd9),
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
+                Zobrist64(0x3c8123ea7b067637),
+            ),
+            (
+                "rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 1 4",
+                Zobrist64(0x5c3f9b829b279560),
+            ),
+        ];
+
+        for (fen, expected) in reference_values {
+            let pos: Chess = fen
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+
+            assert_eq!(
+                pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
+                expected,
+                "{}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_polyglot_hash() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
+            // Only pseudo-legal en passant.
+            "8/8/8/K1pP3r/8/8/8/4k3 w - c6 0 1",
+            // No pawn to capture en passant.
+            "4k3/8/8/2p5/8/8/8/4K3 w - c6 0 1",
+        ] {
+            let pos: Zobrist<Chess, Zobrist64> = fen
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+            assert_eq!(
+                pos.polyglot_hash(),
+                pos.zobrist_hash::<Zobrist64>(EnPassantMode::PseudoLegal),
+                "{fen}"
+            );
+        }
+
+        assert_eq!(
+            Zobrist::<Chess, Zobrist64>::default().polyglot_hash(),
+            Zobrist64(0x463b96181691fc9c)
+        );
+    }
+
+    #[cfg(feature = "variant")]
+    #[test]
+    fn test_variants_not_distinguished() {
+        // Useful when indexing a table of opening names by Zorbist hash.
+        let chess: Zobrist128 = Chess::default().zobrist_hash(EnPassantMode::Legal);
+        let crazyhouse = crate::variant::Crazyhouse::default().zobrist_hash(EnPassantMode::Legal);
+        let three_check = crate::variant::ThreeCheck::default().zobrist_hash(EnPassantMode::Legal);
+        let king_of_the_hill =
+            crate::variant::KingOfTheHill::default().zobrist_hash(EnPassantMode::Legal);
+        assert_eq!(chess, crazyhouse);
+        assert_eq!(chess, three_check);
+        assert_eq!(chess, king_of_the_hill);
+    }
+
+    #[cfg(feature = "variant")]
+    #[test]
+    fn test_promoted_only_hashed_in_crazyhouse() {
+        let fen: Fen = "4k3/8/8/8/8/8/8/3Q~K3[] w - - 0 1"
+            .parse()
+            .expect("valid fen");
+        let chess: Chess = fen
+            .clone()
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+        let crazyhouse: crate::variant::Crazyhouse = fen
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+
+        assert_eq!(chess.promoted(), Bitboard::EMPTY);
+        assert_eq!(crazyhouse.promoted(), Bitboard::from(Square::D1));
+
+        let chess_hash: Zobrist64 = chess.zobrist_hash(EnPassantMode::Legal);
+        assert_eq!(
+            chess_hash,
+            "4k3/8/8/8/8/8/8/3QK3 w - - 0 1"
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position::<Chess>(CastlingMode::Standard)
+                .expect("legal position")
+                .zobrist_hash(EnPassantMode::Legal)
+        );
+        assert_eq!(
+            crazyhouse.zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
+            chess_hash ^ Zobrist64::zobrist_for_promoted(Square::D1)
+        );
+    }
+
+    #[test]
+    fn test_incremental_promotion_capture() {
+        use crate::uci::UciMove;
+
+        let start: Zobrist<Chess, Zobrist64> = "r3k2r/1P4P1/8/8/8/8/8/4K3 w kq - 0 1"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+
+        for (uci, castling_rights) in [
+            ("b7a8q", Bitboard::from(Square::H8)),
+            ("b7a8n", Bitboard::from(Square::H8)),
+            ("g7h8r", Bitboard::from(Square::A8)),
+            ("g7h8b", Bitboard::from(Square::A8)),
+        ] {
+            let mut pos = start.clone();
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal uci");
+            assert!(m.is_capture() && m.is_promotion());
+            pos.play_unchecked(&m);
+
+            assert_eq!(
+                pos.castles().castling_rights(),
+                castling_rights,
+                "after {uci}"
+            );
+            assert_eq!(
+                pos.hash(),
+                pos.clone()
+                    .into_inner()
+                    .zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
+                "after {uci}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_incremental_chess960_rook_captures() {
+        use crate::uci::UciMove;
+
+        let mut pos: Zobrist<Chess, Zobrist64> = "1r3kr1/8/8/8/8/8/8/1R3KR1 w GBgb - 0 1"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Chess960)
+            .expect("legal position");
+
+        for (uci, castling_rights) in [
+            (
+                "b1b8",
+                Bitboard::from(Square::G1) | Bitboard::from(Square::G8),
+            ),
+            ("f8e7", Bitboard::from(Square::G1)),
+            ("g1g8", Bitboard::EMPTY),
+        ] {
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal uci");
+            pos.play_unchecked(&m);
+
+            assert_eq!(
+                pos.castles().castling_rights(),
+                castling_rights,
+                "after {uci}"
+            );
+            assert_eq!(
+                pos.hash(),
+                pos.clone()
+                    .into_inner()
+                    .zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
+                "after {uci}"
+            );
+        }
+    }
+
+    #[cfg(feature = "variant")]
+    #[test]
+    fn test_incremental_three_check() {
+        use crate::{uci::UciMove, variant::ThreeCheck};
+
+        let mut pos: Zobrist<ThreeCheck, Zobrist64> = Zobrist::default();
+
+        for uci in [
+            "e2e4", "e7e5", "f1c4", "g8f6", "c4f7", "e8f7", "d1h5", "g7g6", "h5e5", "f8e7", "e5f6",
+        ] {
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal uci");
+            let turn = pos.turn();
+            let remaining = *pos.remaining_checks().expect("three-check").get(turn);
+            let gives_check = pos.gives_check(&m);
+
+            pos.play_unchecked(&m);
+
+            assert_eq!(
+                u32::from(remaining)
+                    - u32::from(*pos.remaining_checks().expect("three-check").get(turn)),
+                u32::from(gives_check),
+                "after {uci}"
+            );
+            assert_eq!(
+                pos.hash(),
+                pos.clone()
+                    .into_inner()
+                    .zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
+                "after {uci}"
+            );
+        }
+
+        assert!(pos.is_variant_end());
+        assert_eq!(
+            pos.remaining_checks(),
+            Some(&ByColor {
+                white: RemainingChecks::new(0),
+                black: RemainingChecks::new(3),
+            })
+        );
+    }
+
+    #[cfg(feature = "variant")]
+    #[test]
+    fn test_remaining_checks_distinguished() {
+        use crate::variant::ThreeCheck;
+
+        let hashes = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 2+3 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 1+3 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+2 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+1 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 2+2 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 1+2 0 1",
+        ]
+        .map(|fen| {
+            let pos: Zobrist<ThreeCheck, Zobrist64> = fen
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+            assert_eq!(
+                pos.hash(),
+                pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal)
+            );
+            pos.hash()
+        });
+
+        for (i, a) in hashes.iter().enumerate() {
+            for b in &hashes[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[cfg(feature = "variant")]
+    #[test]
+    fn test_incremental_crazyhouse_drop() {
+        use crate::variant::Crazyhouse;
+
+        let pos: Crazyhouse = "r1bqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKB1R[Nn] w KQkq - 0 4"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+        let mut pos: Zobrist<Crazyhouse, Zobrist64> = Zobrist::new(pos);
+
+        for m in [
+            Move::Put {
+                role: Role::Knight,
+                to: Square::D5,
+            },
+            Move::Put {
+                role: Role::Knight,
+                to: Square::B4,
+            },
+            Move::Normal {
+                role: Role::Knight,
+                from: Square::D5,
+                capture: Some(Role::Pawn),
+                to: Square::C7,
+                promotion: None,
+            },
+            Move::Normal {
+                role: Role::King,
+                from: Square::E8,
+                capture: None,
+                to: Square::E7,
+                promotion: None,
+            },
+            Move::Normal {
+                role: Role::Knight,
+                from: Square::C7,
+                capture: Some(Role::Rook),
+                to: Square::A8,
+                promotion: None,
+            },
+        ] {
+            assert!(pos.is_legal(&m), "{m:?} is legal");
+            pos.play_unchecked(&m);
+            assert_eq!(
+                pos.hash(),
+                pos.clone()
+                    .into_inner()
+                    .zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
+                "after {m:?}"
+            );
+        }
+
+        assert_eq!(pos.pockets().map(|p| p.white.rook), Some(1));
+    }
+
+    #[cfg(feature = "variant")]
+    #[test]
+    fn test_pockets_distinguished() {
+        use crate::variant::Crazyhouse;
+
+        let hashes = [
+            "4k3/8/8/8/8/8/8/4K3[] w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K3[N] w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K3[n] w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K3[NN] w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K3[Nn] w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K3[Bn] w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K3[PPPPPPPPPPPPPPPP] w - - 0 1",
+            "4k3/8/8/8/8/8/8/4K3[PPPPPPPPPPPPPPP] w - - 0 1",
+        ]
+        .map(|fen| {
+            let pos: Zobrist<Crazyhouse, Zobrist64> = fen
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+            assert_eq!(
+                pos.hash(),
+                pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal)
+            );
+            pos.hash()
+        });
+
+        for (i, a) in hashes.iter().enumerate() {
+            for b in &hashes[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_incremental_move_variants() {
+        for (fen, m) in [
+            // Quiet move.
+            (
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+                Move::Normal {
+                    role: Role::Knight,
+                    from: Square::G1,
+                    capture: None,
+                    to: Square::F3,
+                    promotion: None,
+                },
+            ),
+            // Double pawn push that creates a legal en passant square.
+            (
+                "rnbqkbnr/pppppppp/8/4P3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2",
+                Move::Normal {
+                    role: Role::Pawn,
+                    from: Square::D7,
+                    capture: None,
+                    to: Square::D5,
+                    promotion: None,
+                },
+            ),
+            // Capture that removes a castling right.
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+                Move::Normal {
+                    role: Role::Rook,
+                    from: Square::A1,
+                    capture: Some(Role::Rook),
+                    to: Square::A8,
+                    promotion: None,
+                },
+            ),
+            // Promotion.
+            (
+                "8/4P3/8/8/8/2k5/8/4K3 w - - 0 1",
+                Move::Normal {
+                    role: Role::Pawn,
+                    from: Square::E7,
+                    capture: None,
+                    to: Square::E8,
+                    promotion: Some(Role::Queen),
+                },
+            ),
+            // Capture-promotion.
+            (
+                "3r4/4P3/8/8/8/2k5/8/4K3 w - - 0 1",
+                Move::Normal {
+                    role: Role::Pawn,
+                    from: Square::E7,
+                    capture: Some(Role::Rook),
+                    to: Square::D8,
+                    promotion: Some(Role::Knight),
+                },
+            ),
+            // Castling kingside.
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+                Move::Castle {
+                    king: Square::E1,
+                    rook: Square::H1,
+                },
+            ),
+            // Castling queenside.
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1",
+                Move::Castle {
+                    king: Square::E8,
+                    rook: Square::A8,
+                },
+            ),
+            // En passant, clearing the previous en passant square.
+            (
+                "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+                Move::EnPassant {
+                    from: Square::E5,
+                    to: Square::F6,
+                },
+            ),
+        ] {
+            let mut pos: Zobrist<Chess, Zobrist64> = fen
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+            assert!(pos.is_legal(&m), "{m:?} is legal in {fen}");
+            pos.play_unchecked(&m);
+            assert_eq!(
+                pos.hash(),
+                pos.clone()
+                    .into_inner()
+                    .zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
+                "{m:?} in {fen}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_after_san_line() {
+        use crate::uci::UciMove;
+
+        let start: Zobrist<Chess, Zobrist64> = Zobrist::default();
+
+        let mut expected = start.clone();
+        for uci in ["e2e4", "e7e5", "g1f3"] {
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&expected)
+                .expect("legal uci");
+            expected.play_unchecked(&m);
+        }
+
+        assert_eq!(
+            hash_after_san_line(&start, &["e4", "e5", "Nf3"]),
+            Ok(expected.hash())
+        );
+        assert_eq!(hash_after_san_line(&start, &[]), Ok(start.hash()));
+        assert_eq!(
+            hash_after_san_line(&start, &["e4", "e4"]),
+            Err(SanError::IllegalSan)
+        );
+        assert_eq!(
+            hash_after_san_line(&start, &["e4", "??"]),
+            Err(SanError::IllegalSan)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_format_hash() {
+        use alloc::format;
+
+        let pos: Zobrist<Chess, Zobrist64> =
+            "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+
+        assert_eq!(format!("{pos:016x}"), format!("{:016x}", pos.hash()));
+        assert_eq!(format!("{pos:016X}"), format!("{:016X}", pos.hash()));
+        assert_eq!(format!("{pos:x}"), "756b94461c50fb0");
+        assert_eq!(
+            format!("{pos}"),
+            "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 756b94461c50fb0"
+        );
+    }
+
+    #[test]
+    fn test_from_setup_checked() {
+        let setup = "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_setup();
+
+        let pos = Zobrist::<Chess, Zobrist64>::from_setup_checked(
+            setup.clone(),
+            CastlingMode::Standard,
+            Zobrist64(0x756b94461c50fb0),
+        )
+        .expect("matching hash");
+        assert_eq!(pos.hash(), Zobrist64(0x756b94461c50fb0));
+
+        match Zobrist::<Chess, Zobrist64>::from_setup_checked(
+            setup,
+            CastlingMode::Standard,
+            Zobrist64(0x463b96181691fc9c),
+        ) {
+            Err(ZobristSetupError::HashMismatch { pos, expected }) => {
+                assert_eq!(pos.hash(), Zobrist64(0x756b94461c50fb0));
+                assert_eq!(expected, Zobrist64(0x463b96181691fc9c));
+            }
+            _ => panic!("expected hash mismatch"),
+        }
+
+        assert!(matches!(
+            Zobrist::<Chess, Zobrist64>::from_setup_checked(
+                Setup::empty(),
+                CastlingMode::Standard,
+                Zobrist64(0),
+            ),
+            Err(ZobristSetupError::Position(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_setup_error_hash() {
+        // Recoverable: The position carries the correct hash.
+        let setup = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN1 w KQkq - 0 1"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_setup();
+        let err = Zobrist::<Chess, Zobrist64>::from_setup(setup, CastlingMode::Standard)
+            .expect_err("invalid castling rights");
+        assert_eq!(err.kinds(), PositionErrorKinds::INVALID_CASTLING_RIGHTS);
+        let pos = err.ignore_invalid_castling_rights().expect("recoverable");
+        assert_eq!(
+            pos.hash(),
+            pos.clone()
+                .into_inner()
+                .zobrist_hash::<Zobrist64>(EnPassantMode::Legal)
+        );
+
+        // Not recoverable: The hash is not computed.
+        let err = Zobrist::<Chess, Zobrist64>::from_setup(Setup::empty(), CastlingMode::Standard)
+            .expect_err("missing kings");
+        assert!(err.kinds().contains(PositionErrorKinds::MISSING_KING));
+        assert_eq!(err.pos.hash(), Zobrist64::default());
+    }
+
+    #[test]
+    fn test_board_hash() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b KQkq - 0 1",
+        ] {
+            let pos: Chess = fen
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+
+            // Remove all components but the piece placement.
+            let mut expected = pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal);
+            if pos.turn() == Color::White {
+                expected ^= Zobrist64::zobrist_for_white_turn();
+            }
+            expected ^= hash_castles::<Zobrist64>(pos.castles());
+            if let Some(sq) = pos.legal_ep_square() {
+                expected ^= Zobrist64::zobrist_for_en_passant_file(sq.file());
+            }
+
+            assert_eq!(board_hash::<Zobrist64>(pos.board()), expected, "{fen}");
+        }
+    }
+
+    #[test]
+    fn test_successors() {
+        let pos: Zobrist<Chess, Zobrist64> =
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+
+        assert_eq!(pos.successors().count(), pos.legal_moves().len());
+        for (m, child) in pos.successors() {
+            assert_eq!(
+                child.hash(),
+                child.zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
+                "{m}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_hash_ignores_clocks() {
+        use crate::uci::UciMove;
+
+        let a: Chess = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+        let b: Chess = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 47 120"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+        assert_ne!(a.halfmoves(), b.halfmoves());
+        assert_ne!(a.fullmoves(), b.fullmoves());
+        assert_eq!(
+            a.zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
+            b.zobrist_hash::<Zobrist64>(EnPassantMode::Legal)
+        );
+        assert_eq!(
+            Zobrist::<_, Zobrist64>::new(a).hash(),
+            Zobrist::<_, Zobrist64>::new(b).hash()
+        );
+
+        // The incremental hash also ignores the clocks, which change with
+        // every move.
+        let start: Zobrist<Chess, Zobrist64> = Zobrist::default();
+        let mut pos = start.clone();
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal move");
+            pos.play_unchecked(&m);
+        }
+        assert_eq!(pos.halfmoves(), 4);
+        assert_eq!(pos.hash(), start.hash());
+    }
+
+    #[test]
+    fn test_repetition_key() {
+        use crate::uci::UciMove;
+
+        let play = |ucis: &[&str]| {
+            let mut pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+            for uci in ucis {
+                let m = uci
+                    .parse::<UciMove>()
+                    .expect("valid uci")
+                    .to_move(&pos)
+                    .expect("legal uci");
+                pos.play_unchecked(&m);
+            }
+            pos
+        };
+
+        let a = play(&["e2e4", "e7e5", "g1f3", "b8c6", "f3g1", "c6b8", "g1f3"]);
+        let b = play(&["g1f3", "e7e5", "e2e4"]);
+        assert_eq!(a.repetition_key(), b.repetition_key());
+        assert_ne!(a.halfmoves(), b.halfmoves());
+        assert_ne!(a.fullmoves(), b.fullmoves());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_game_fingerprint() {
+        use crate::uci::UciMove;
+
+        let start: Zobrist<Chess, Zobrist64> = Zobrist::default();
+        let line = |ucis: &[&str]| {
+            let mut pos = start.clone();
+            let mut moves = alloc::vec::Vec::new();
+            for uci in ucis {
+                let m = uci
+                    .parse::<UciMove>()
+                    .expect("valid uci")
+                    .to_move(&pos)
+                    .expect("legal uci");
+                pos.play_unchecked(&m);
+                moves.push(m);
+            }
+            (pos, moves)
+        };
+
+        let (a, a_moves) = line(&["g1f3", "g8f6", "b1c3", "b8c6"]);
+        let (_, same_moves) = line(&["g1f3", "g8f6", "b1c3", "b8c6"]);
+        let (b, b_moves) = line(&["b1c3", "b8c6", "g1f3", "g8f6"]);
+
+        // Transposition to the same final position.
+        assert_eq!(a.hash(), b.hash());
+
+        assert_eq!(
+            game_fingerprint(&start, &a_moves),
+            game_fingerprint(&start, &same_moves)
+        );
+        assert_ne!(
+            game_fingerprint(&start, &a_moves),
+            game_fingerprint(&start, &b_moves)
+        );
+    }
+
+    #[test]
+    fn test_zobrist_tables() {
+        let tables = zobrist_tables::<Zobrist64>();
+        for sq in Square::ALL {
+            for color in Color::ALL {
+                for role in Role::ALL {
+                    assert_eq!(
+                        tables.piece_square.get(color).get(role)[usize::from(sq)],
+                        Zobrist64::zobrist_for_piece(sq, role.of(color))
+                    );
+                }
+            }
+        }
+
+        // Rebuild the hash of the starting position from the tables.
+        let pos = Chess::default();
+        let mut hash = tables.white_turn;
+        for (sq, piece) in pos.board().clone() {
+            hash ^= tables.piece_square.get(piece.color).get(piece.role)[usize::from(sq)];
+        }
+        for color in Color::ALL {
+            for side in [CastlingSide::KingSide, CastlingSide::QueenSide] {
+                hash ^= tables.castling.get(color)[side as usize];
+            }
+        }
+        assert_eq!(hash, pos.zobrist_hash(EnPassantMode::Legal));
+        assert_eq!(hash, Zobrist64(0x463b96181691fc9c));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_keyed() {
+        use std::{format, string::ToString as _, sync::OnceLock};
+
+        // Keys taken from the built-in tables reproduce the built-in hashes.
+        struct Builtin;
+
+        impl ZobristKeys for Builtin {
+            fn tables() -> &'static ZobristTables<Zobrist64> {
+                static TABLES: OnceLock<ZobristTables<Zobrist64>> = OnceLock::new();
+                TABLES.get_or_init(zobrist_tables::<Zobrist64>)
+            }
+        }
+
+        struct Seeded;
+
+        impl ZobristKeys for Seeded {
+            fn tables() -> &'static ZobristTables<Zobrist64> {
+                static TABLES: OnceLock<ZobristTables<Zobrist64>> = OnceLock::new();
+                TABLES.get_or_init(|| ZobristTables::from_seed(7))
+            }
+        }
+
+        let mut rng = RandomMoves::new(7);
+        for _ in 0..8 {
+            let mut pos: Zobrist<Chess, Keyed<Seeded>> = Zobrist::default();
+            for _ in 0..100 {
+                let Some(m) = rng.pick(&pos, true) else {
+                    break;
+                };
+                pos.play_unchecked(&m);
+                assert_eq!(pos.hash(), pos.zobrist_hash(EnPassantMode::Legal));
+                assert_eq!(
+                    pos.zobrist_hash::<Keyed<Builtin>>(EnPassantMode::Legal)
+                        .into_inner(),
+                    pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal)
+                );
+            }
+        }
+
+        #[cfg(feature = "variant")]
+        {
+            use crate::variant::{Crazyhouse, ThreeCheck};
+
+            let pos: Crazyhouse = "r~n~b1kbn1/pppppp2/8/8/8/8/PPPPPPPP/4K3[RNBQBNRq] w - - 0 1"
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+            assert_eq!(
+                pos.zobrist_hash::<Keyed<Builtin>>(EnPassantMode::Legal)
+                    .into_inner(),
+                pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal)
+            );
+
+            let pos: ThreeCheck = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 2+1 0 1"
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+            assert_eq!(
+                pos.zobrist_hash::<Keyed<Builtin>>(EnPassantMode::Legal)
+                    .into_inner(),
+                pos.zobrist_hash::<Zobrist64>(EnPassantMode::Legal)
+            );
+        }
+
+        assert_ne!(
+            ZobristTables::from_seed(7).white_turn,
+            ZobristTables::from_seed(8).white_turn
+        );
+
+        // Formatted like the built-in hashes.
+        let keyed: Zobrist<Chess, Keyed<Builtin>> = Zobrist::default();
+        let builtin: Zobrist<Chess, Zobrist64> = Zobrist::default();
+        assert_eq!(keyed.to_string(), builtin.to_string());
+        assert_eq!(format!("{keyed:X}"), format!("{builtin:X}"));
+        assert_eq!(format!("{:?}", keyed.hash()), "Keyed(0x463b96181691fc9c)");
+    }
+
+    #[test]
+    fn test_region_hash() {
+        let pos: Zobrist<Chess, Zobrist64> =
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+
+        // The full region is the placement component of the full hash.
+        let placement = pos.hash()
+            ^ Zobrist64::zobrist_for_white_turn()
+            ^ hash_castles::<Zobrist64>(pos.castles());
+        assert_eq!(pos.region_hash(Bitboard::FULL), placement);
+        assert_eq!(pos.region_hash(Bitboard::FULL), board_hash(pos.board()));
+
+        // Disjoint regions combine to the full region.
+        assert_eq!(
+            pos.region_hash(Bitboard::NORTH) ^ pos.region_hash(Bitboard::SOUTH),
+            placement
+        );
+    }
+
+    #[test]
+    fn test_pawn_hash() {
+        use crate::uci::UciMove;
+
+        let mut pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+        assert_eq!(pos.pawn_hash(), pos.region_hash(pos.board().pawns()));
+
+        // Pawn moves, en passant, piece moves, captures of pawns, and a
+        // capturing promotion.
+        for uci in [
+            "e2e4", "d7d5", "e4e5", "f7f5", "e5f6", "g8h6", "f6g7", "b8c6", "g7h8q", "c6d4",
+            "g1f3", "d4f3", "d1f3",
+        ] {
+            let before = pos.pawn_hash();
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal uci");
+            let pawns = pos.board().pawns();
+            pos.play_unchecked(&m);
+
+            assert_eq!(
+                pos.pawn_hash(),
+                pos.region_hash(pos.board().pawns()),
+                "{uci}"
+            );
+            assert_eq!(
+                pos.pawn_hash() == before,
+                pos.board().pawns() == pawns,
+                "{uci}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_material_hash() {
+        use crate::uci::UciMove;
+
+        let mut pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+        assert_eq!(pos.material_hash(), hash_material(pos.board()));
+
+        // Quiet moves, captures, en passant, and a capturing promotion.
+        for uci in [
+            "e2e4", "d7d5", "e4d5", "e7e5", "d5e6", "b8c6", "e6f7", "e8e7", "f7g8q",
+        ] {
+            let before = pos.material_hash();
+            let material = pos.board().material();
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal uci");
+            pos.play_unchecked(&m);
+
+            assert_eq!(pos.material_hash(), hash_material(pos.board()), "{uci}");
+            assert_eq!(
+                pos.material_hash() == before,
+                pos.board().material() == material,
+                "{uci}"
+            );
+        }
+
+        // Equal material with different placement.
+        let a: Zobrist<Chess, Zobrist64> = "4k3/8/8/8/8/8/3R4/4K3 w - - 0 1"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+        let b: Zobrist<Chess, Zobrist64> = "4k3/8/8/8/8/8/8/R3K3 b - - 0 1"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+        let c: Zobrist<Chess, Zobrist64> = "4k3/8/8/8/8/8/8/r3K3 w - - 0 1"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+        assert_eq!(a.material_hash(), b.material_hash());
+        assert_ne!(a.material_hash(), c.material_hash());
+
+        // Material keys do not share the key space of the piece keys.
+        for sq in Square::ALL {
+            for piece in [Color::White.king(), Color::Black.rook()] {
+                assert!(Square::ALL.into_iter().all(|other| {
+                    Zobrist64::zobrist_for_material(piece, usize::from(sq))
+                        != Zobrist64::zobrist_for_piece(other, piece)
+                }));
+            }
+        }
+
+        // Narrower material hashes are truncations of wider ones.
+        let wide: Zobrist<Chess, Zobrist128> = Zobrist::new(a.into_inner());
+        let narrow: Zobrist<Chess, Zobrist32> = Zobrist::new(wide.as_inner().clone());
+        assert_eq!(Zobrist64::from(wide.material_hash()), b.material_hash());
+        assert_eq!(
+            Zobrist32::from(Zobrist64::from(wide.material_hash())),
+            narrow.material_hash()
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_play_and_unplay() {
+        use alloc::string::ToString as _;
+
+        use crate::uci::UciMove;
+
+        let mut pos: Zobrist<Chess, Zobrist64> =
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+
+        let mut stack = alloc::vec::Vec::new();
+        let mut hashes = alloc::vec::Vec::new();
+        let mut setups = alloc::vec::Vec::new();
+        for uci in ["e1g1", "h3g2", "a2a4", "b4a3", "f3f6", "g2f1q"] {
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal uci");
+            hashes.push((pos.hash(), pos.pawn_hash(), pos.material_hash()));
+            setups.push(pos.as_inner().clone().into_setup(EnPassantMode::Always));
+            stack.push(pos.play_and_remember(&m));
+        }
+
+        while let Some(undo) = stack.pop() {
+            pos.unplay(undo);
+            assert_eq!(
+                (pos.hash(), pos.pawn_hash(), pos.material_hash()),
+                hashes.pop().expect("hashes")
+            );
+            assert_eq!(
+                pos.as_inner().clone().into_setup(EnPassantMode::Always),
+                setups.pop().expect("setups")
+            );
+            assert_eq!(pos.hash(), pos.zobrist_hash(EnPassantMode::Legal));
+        }
+        assert_eq!(
+            Fen::from_position(pos.into_inner(), EnPassantMode::Legal).to_string(),
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"
+        );
+    }
+
+    #[test]
+    fn test_hash_after() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/Pp2P3/2N2Q1p/1PPBBPPP/R3K2R b KQkq a3 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            // Double pawn pushes next to pinned pawns.
+            "8/8/8/8/k2p3R/8/2P5/4K3 w - - 0 1",
+            "4k3/8/8/8/1K3p1r/8/4P3/8 w - - 0 1",
+            // Chess960 castling.
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+            "1r2k3/8/8/8/8/8/8/R3K2R w KQ - 0 1",
+        ] {
+            let pos: Zobrist<Chess, Zobrist64> = fen
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Chess960)
+                .expect("legal position");
+            for m in pos.legal_moves() {
+                let mut child = pos.clone();
+                child.play_unchecked(&m);
+                assert_eq!(pos.hash_after(&m), child.hash(), "{fen}: {m}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_no_drops_in_chess() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ] {
+            let pos: Zobrist<Chess> = fen
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+            for m in pos.legal_moves() {
+                assert!(!matches!(m, Move::Put { .. }), "{m:?} in {fen}");
+                let mut after = pos.clone();
+                after.play_unchecked(&m);
+                assert_eq!(
+                    after.hash(),
+                    after
+                        .clone()
+                        .into_inner()
+                        .zobrist_hash::<Zobrist64>(EnPassantMode::Legal)
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_legal_moves_cached() {
+        use std::collections::HashMap;
+
+        let pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+        let mut cache = HashMap::new();
+
+        assert_eq!(pos.legal_moves_cached(&mut cache), pos.legal_moves());
+        assert_eq!(pos.legal_moves_cached(&mut cache), pos.legal_moves());
+        assert_eq!(cache.len(), 1);
+
+        // A tampered cache entry is returned as is, proving that the second
+        // lookup does not generate moves.
+        cache.insert(pos.hash(), MoveList::new());
+        assert!(pos.legal_moves_cached(&mut cache).is_empty());
+    }
+
+    #[test]
+    fn test_hash_after_null_move() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3",
+        ] {
+            let pos: Zobrist<Chess, Zobrist64> = fen
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+            let hash = pos.hash();
+            let null_move_hash = pos.hash_after_null_move();
+            assert_eq!(pos.hash(), hash, "not modified");
+
+            let after = pos.swap_turn().expect("swap turn");
+            assert_eq!(null_move_hash, after.hash(), "{fen}");
+            assert_eq!(
+                null_move_hash,
+                after
+                    .into_inner()
+                    .zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
+                "{fen}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_incremental_state() {
+        struct Material {
+            score: i32,
+            white_to_move: bool,
+        }
+
+        fn value(piece: Piece) -> i32 {
+            piece.color.fold_wb(piece.role.value(), -piece.role.value())
+        }
+
+        impl Material {
+            fn of<P: Position>(pos: &P) -> Material {
+                Material {
+                    score: pos.material_balance(),
+                    white_to_move: pos.turn().is_white(),
+                }
+            }
+        }
+
+        impl IncrementalState for Material {
+            fn on_add(&mut self, _sq: Square, piece: Piece) {
+                self.score += value(piece);
+            }
+
+            fn on_remove(&mut self, _sq: Square, piece: Piece) {
+                self.score -= value(piece);
+            }
+
+            fn on_side_flip(&mut self) {
+                self.white_to_move = !self.white_to_move;
+            }
+        }
+
+        let mut pos: Zobrist<Chess> = "r3k2r/1P3ppp/8/3pP3/8/8/5PPP/R3K2R w KQkq d6 0 1"
+            .parse::<Fen>()
+            .expect("valid fen")
+            .into_position(CastlingMode::Standard)
+            .expect("legal position");
+        let mut material = Material::of(&pos);
+
+        for uci in ["e5d6", "e8g8", "b7a8q", "f8a8", "e1c1"] {
+            let m = uci
+                .parse::<crate::uci::UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal uci");
+            pos.play_unchecked_with(&m, &mut material);
+
+            let expected = Material::of(&pos);
+            assert_eq!(material.score, expected.score, "after {uci}");
+            assert_eq!(material.white_to_move, expected.white_to_move);
+            assert_eq!(
+                pos.hash(),
+                pos.clone()
+                    .into_inner()
+                    .zobrist_hash::<Zobrist64>(EnPassantMode::Legal)
+            );
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_find_line_to_hash() {
+        let start: Zobrist<Chess, Zobrist64> = Zobrist::default();
+
+        let mut pos = start.clone();
+        for uci in ["g1f3", "g8f6", "f3g1"] {
+            let m = uci
+                .parse::<crate::uci::UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal uci");
+            pos.play_unchecked(&m);
+        }
+
+        let line = find_line_to_hash(&start, pos.hash(), 3).expect("line found");
+        assert_eq!(line.len(), 3);
+        let mut replayed = start.clone();
+        for m in &line {
+            replayed.play_unchecked(m);
+        }
+        assert_eq!(replayed.hash(), pos.hash());
+
+        assert_eq!(
+            find_line_to_hash(&start, start.hash(), 3),
+            Some(alloc::vec::Vec::new())
+        );
+        assert_eq!(find_line_to_hash(&start, pos.hash(), 2), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_incremental_random_games_128() {
+        use std::collections::HashMap;
+
+        let mut rng = RandomMoves::new(0x2545_f491_4f6c_dd1d);
+        let mut seen: HashMap<Zobrist128, Setup> = HashMap::new();
+
+        for _ in 0..64 {
+            let mut pos: Zobrist<Chess, Zobrist128> = Zobrist::default();
+            for _ in 0..100 {
+                let Some(m) = rng.pick(&pos, false) else {
+                    break;
+                };
+                pos.play_unchecked(&m);
+
+                let hash = pos.hash();
+                let inner = pos.clone().into_inner();
+                assert_eq!(hash, inner.zobrist_hash::<Zobrist128>(EnPassantMode::Legal));
+                assert_eq!(
+                    Zobrist64::from(hash),
+                    inner.zobrist_hash::<Zobrist64>(EnPassantMode::Legal)
+                );
+
+                let mut setup = inner.into_setup(EnPassantMode::Legal);
+                setup.halfmoves = 0;
+                setup.fullmoves = NonZeroU32::MIN;
+                let previous = seen.entry(hash).or_insert_with(|| setup.clone());
+                assert_eq!(*previous, setup, "128 bit collision");
+            }
+        }
+
+        assert!(seen.len() > 1000);
+    }
+
+    #[test]
+    fn test_incremental_random_captures() {
+        // Mirrors the board using only the reported changes.
+        struct Mirror(Board);
+
+        impl IncrementalState for Mirror {
+            fn on_add(&mut self, sq: Square, piece: Piece) {
+                assert_eq!(self.0.piece_at(sq), None, "add {piece:?} on {sq}");
+                self.0.set_piece_at(sq, piece);
+            }
+
+            fn on_remove(&mut self, sq: Square, piece: Piece) {
+                assert_eq!(self.0.remove_piece_at(sq), Some(piece), "remove from {sq}");
+            }
+        }
+
+        let mut rng = RandomMoves::new(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..32 {
+            let mut pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+            let mut mirror = Mirror(pos.board().clone());
+            for _ in 0..150 {
+                let Some(m) = rng.pick(&pos, true) else {
+                    break;
+                };
+                let expected = pos.hash_after(&m);
+                pos.play_unchecked_with(&m, &mut mirror);
+                assert_eq!(pos.hash(), expected);
+
+                assert_eq!(&mirror.0, pos.board());
+                assert_eq!(pos.pawn_hash(), pos.region_hash(pos.board().pawns()));
+                assert_eq!(pos.material_hash(), hash_material(pos.board()));
+                assert_eq!(
+                    pos.hash(),
+                    pos.clone()
+                        .into_inner()
+                        .zobrist_hash::<Zobrist64>(EnPassantMode::Legal)
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "verify")]
+    #[test]
+    fn test_verify_incremental() {
+        for seed in [1, 2, 3] {
+            verify_incremental::<Chess>(8, seed);
+        }
+
+        #[cfg(feature = "variant")]
+        {
+            use crate::variant::{
+                Antichess, Atomic, Crazyhouse, Horde, KingOfTheHill, RacingKings, ThreeCheck,
+            };
+
+            verify_incremental::<Antichess>(8, 1);
+            verify_incremental::<Atomic>(8, 1);
+            verify_incremental::<Crazyhouse>(8, 1);
+            verify_incremental::<Horde>(8, 1);
+            verify_incremental::<KingOfTheHill>(8, 1);
+            verify_incremental::<RacingKings>(8, 1);
+            verify_incremental::<ThreeCheck>(8, 1);
+        }
+    }
+
+    #[test]
+    fn test_incremental_widths() {
+        fn play<V: ZobristValue>(ucis: &[&str]) -> V {
+            let mut pos: Zobrist<Chess, V> = Zobrist::default();
+            for uci in ucis {
+                let m = uci
+                    .parse::<crate::uci::UciMove>()
+                    .expect("valid uci")
+                    .to_move(&pos)
+                    .expect("legal uci");
+                pos.play_unchecked(&m);
+            }
+            pos.hash()
+        }
+
+        // Castling, en passant, and promotion.
+        let ucis = [
+            "e2e4", "d7d5", "e4e5", "f7f5", "e5f6", "g8h6", "f6g7", "e8f7", "g7h8q", "b8c6",
+            "g1f3", "c8e6", "f1c4", "d8d6", "e1h1",
+        ];
+        let h128: Zobrist128 = play(&ucis);
+        let h64: Zobrist64 = play(&ucis);
+        let h32: Zobrist32 = play(&ucis);
+        let h16: Zobrist16 = play(&ucis);
+        let h8: Zobrist8 = play(&ucis);
+        assert_eq!(Zobrist64::from(h128), h64);
+        assert_eq!(Zobrist32::from(h64), h32);
+        assert_eq!(Zobrist16::from(h32), h16);
+        assert_eq!(Zobrist8::from(h16), h8);
+    }
+
+    #[test]
+    fn test_play_and_play_unchecked() {
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            // Castling.
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1",
+            // Promotions.
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1",
+            // En passant.
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "8/8/8/2k5/3Pp3/8/8/4K2Q b - d3 0 1",
+        ] {
+            let pos: Zobrist<Chess, Zobrist64> = fen
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Standard)
+                .expect("legal position");
+
+            for m in pos.legal_moves() {
+                let checked = pos.clone().play(&m).expect("legal move");
+                let mut unchecked = pos.clone();
+                unchecked.play_unchecked(&m);
+
+                assert_eq!(checked.hash(), unchecked.hash(), "{m} in {fen}");
+                assert_eq!(
+                    checked.hash(),
+                    checked
+                        .into_inner()
+                        .zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
+                    "{m} in {fen}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_full_pockets() {
+        // 8/8/8/7k/8/8/3K4/8[ppppppppppppppppnnnnbbbbrrrrqq] w - - 0 54
+        for color in Color::ALL {
+            assert_ne!(
+                Zobrist64::zobrist_for_pocket(color, Role::Pawn, 16),
+                Zobrist64::zobrist_for_pocket(color, Role::Pawn, 15)
+            );
+            assert_ne!(
+                Zobrist64::zobrist_for_pocket(color, Role::Queen, 2),
+                Zobrist64::zobrist_for_pocket(color, Role::Queen, 1)
+            );
+        }
+    }
+
+    #[cfg(feature = "nohash-hasher")]
+    #[test]
+    fn test_nohash_hasher() {
+        let mut hasher = nohash_hasher::NoHashHasher::<Zobrist128>::default();
+        Zobrist128(128).hash(&mut hasher);
+        assert_eq!(hasher.finish(), 128);
+
+        let mut hasher = nohash_hasher::NoHashHasher::<Zobrist64>::default();
+        Zobrist64(64).hash(&mut hasher);
+        assert_eq!(hasher.finish(), 64);
+
+        let mut hasher = nohash_hasher::NoHashHasher::<Zobrist32>::default();
+        Zobrist32(32).hash(&mut hasher);
+        assert_eq!(hasher.finish(), 32);
+
+        let mut hasher = nohash_hasher::NoHashHasher::<Zobrist16>::default();
+        Zobrist16(16).hash(&mut hasher);
+        assert_eq!(hasher.finish(), 16);
+
+        let mut hasher = nohash_hasher::NoHashHasher::<Zobrist8>::default();
+        Zobrist8(8).hash(&mut hasher);
+        assert_eq!(hasher.finish(), 8);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_transposition_table() {
+        let mut tt = TranspositionTable::with_replacement(1, Replacement::PreferDepth);
+        assert_eq!(tt.get(Zobrist64(1)), None);
+        assert!(tt.insert(Zobrist64(1), 3, "deep"));
+        assert!(!tt.insert(Zobrist64(2), 2, "shallow"));
+        assert_eq!(tt.get(Zobrist64(1)), Some(&"deep"));
+        assert_eq!(tt.get(Zobrist64(2)), None);
+        assert!(tt.insert(Zobrist64(1), 1, "same key"));
+        assert_eq!(tt.depth(Zobrist64(1)), Some(1));
+        assert!(tt.insert(Zobrist64(2), 1, "as deep"));
+        assert_eq!(tt.get(Zobrist64(1)), None);
+        assert_eq!(tt.get(Zobrist64(2)), Some(&"as deep"));
+
+        let mut tt = TranspositionTable::new(1);
+        tt.insert(Zobrist64(1), 3, "deep");
+        assert!(tt.insert(Zobrist64(2), 2, "shallow"));
+        assert_eq!(tt.get(Zobrist64(2)), Some(&"shallow"));
+        tt.clear();
+        assert_eq!(tt.get(Zobrist64(2)), None);
+
+        assert_eq!(tt_index(Zobrist64(0), 100), 0);
+        assert_eq!(tt_index(Zobrist64(1 << 63), 100), 50);
+        assert_eq!(tt_index(Zobrist64(u64::MAX), 100), 99);
+    }
+
+    #[cfg(all(feature = "alloc", target_has_atomic = "64"))]
+    #[test]
+    fn test_lockless_table() {
+        let mut tt = LocklessTable::new(2);
+        assert_eq!(tt.load(Zobrist64(0)), None);
+        tt.store(Zobrist64(0), 42);
+        tt.store(Zobrist64(u64::MAX), 7);
+        assert_eq!(tt.load(Zobrist64(0)), Some(42));
+        assert_eq!(tt.load(Zobrist64(u64::MAX)), Some(7));
+        assert_eq!(tt.load(Zobrist64(1)), None);
+
+        // A torn write is a miss.
+        let (_, data) = &tt.entries[0];
+        data.store(43, Ordering::Relaxed);
+        assert_eq!(tt.load(Zobrist64(0)), None);
+
+        tt.clear();
+        assert_eq!(tt.load(Zobrist64(u64::MAX)), None);
+
+        // The empty sentinel.
+        tt.store(Zobrist64(0), 0);
+        assert_eq!(tt.load(Zobrist64(0)), None);
+        tt.store(Zobrist64(u64::MAX), 0);
+        assert_eq!(tt.load(Zobrist64(u64::MAX)), Some(0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_cuckoo() {
+        let cuckoo = Cuckoo::shared();
+        assert_eq!(cuckoo.moves.iter().filter(|m| m.is_some()).count(), 3668);
+
+        let key = Zobrist64::zobrist_for_piece(Square::G1, Color::White.knight())
+            ^ Zobrist64::zobrist_for_piece(Square::F3, Color::White.knight())
+            ^ Zobrist64::zobrist_for_white_turn();
+        assert_eq!(cuckoo.get(key), Some((Square::G1, Square::F3)));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_has_game_cycle() {
+        use crate::uci::UciMove;
+
+        let mut pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+        let mut history = RepetitionTable::new();
+        history.push(pos.hash());
+        let mut cycles = alloc::vec::Vec::new();
+        for uci in [
+            "g1f3", "g8f6", "f3g1", "f6g8", "e2e4", "b8c6", "f1e2", "c6b8", "e2f1",
+        ] {
+            cycles.push(pos.has_game_cycle(&history, 100));
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal move");
+            pos.play_tracked(&m, &mut history);
+        }
+        cycles.push(pos.has_game_cycle(&history, 100));
+        assert_eq!(
+            cycles,
+            [false, false, false, true, true, false, false, false, true, true]
+        );
+
+        // Before the root, only positions that already repeated count.
+        assert!(!pos.has_game_cycle(&history, 0));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_repetition_table_tracked() {
+        use crate::uci::UciMove;
+
+        let mut pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+        let mut table = RepetitionTable::new();
+        table.push(pos.hash());
+        assert_eq!(table.count_last(), 1);
+
+        let mut play = |ucis: &[&str]| {
+            for uci in ucis {
+                let m = uci.parse::<UciMove>().unwrap().to_move(&pos).unwrap();
+                pos.play_tracked(&m, &mut table);
+            }
+            table.count_last()
+        };
+
+        assert_eq!(play(&["e2e4", "e7e5"]), 1);
+        let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+        assert_eq!(play(&shuffle), 2);
+        assert_eq!(play(&shuffle), 3);
+        assert_eq!(play(&shuffle), 4);
+        assert_eq!(play(&shuffle), 5);
+        assert_eq!(play(&shuffle[..1]), 5);
+
+        // Losing castling rights is irreversible.
+        assert_eq!(play(&["e8e7"]), 1);
+        assert!(!table.is_threefold());
+
+        let mut wide: RepetitionTable<Zobrist128> = RepetitionTable::default();
+        for _ in 0..3 {
+            wide.push(Zobrist128(u128::MAX));
+        }
+        assert!(wide.is_threefold());
+        wide.reset(Zobrist128(1));
+        assert_eq!(wide.count(Zobrist128(u128::MAX)), 0);
+    }
+
+    #[cfg(feature = "nohash-hasher")]
+    #[test]
+    fn test_nohash_hasher_zobrist() {
+        use core::hash::BuildHasher as _;
+
+        let pos: Zobrist<Chess, Zobrist64> = Zobrist::default();
+        let build_hasher = nohash_hasher::BuildNoHashHasher::<Zobrist<Chess, Zobrist64>>::default();
+        assert_eq!(build_hasher.hash_one(&pos), 0x463b96181691fc9c);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_zobrist_in_hash_set() {
+        use std::collections::HashSet;
+
+        use crate::uci::UciMove;
+
+        let start: Zobrist<Chess, Zobrist64> = Zobrist::default();
+        let mut pos = start.clone();
+        let mut seen = HashSet::new();
+        assert!(seen.insert(pos.clone()));
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            let m = uci.parse::<UciMove>().unwrap().to_move(&pos).unwrap();
+            pos.play_unchecked(&m);
+            assert!(seen.insert(pos.clone()) != (pos == start));
+        }
+        assert_eq!(seen.len(), 4);
+        assert_eq!(pos.as_inner(), start.as_inner());
+        assert_eq!(*pos, Chess::default());
+    }
+
+    #[test]
+    fn test_incremental_chess960_castling() {
+        use crate::uci::UciMove;
+
+        let mut pos: Zobrist<Chess, Zobrist64> =
+            "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9"
+                .parse::<Fen>()
+                .expect("valid fen")
+                .into_position(CastlingMode::Chess960)
+                .expect("legal position");
+
+        for (uci, castling_rights) in [
+            // Moving the rook on the f-file loses only queenside rights.
+            (
+                "f1f2",
+                Bitboard::from(Square::H1)
+                    | Bitboard::from(Square::F8)
+                    | Bitboard::from(Square::H8),
+            ),
+            (
+                "f8e8",
+                Bitboard::from(Square::H1) | Bitboard::from(Square::H8),
+            ),
+            // Castling with the king already on its destination square.
+            ("g1h1", Bitboard::from(Square::H8)),
+        ] {
+            let m = uci
+                .parse::<UciMove>()
+                .expect("valid uci")
+                .to_move(&pos)
+                .expect("legal uci");
+            pos.play_unchecked(&m);
+
+            assert_eq!(
+                pos.castles().castling_rights(),
+                castling_rights,
+                "after {uci}"
+            );
+            assert_eq!(
+                pos.hash(),
+                pos.clone()
+                    .into_inner()
+                    .zobrist_hash::<Zobrist64>(EnPassantMode::Legal),
+                "after {uci}"
+            );
+        }
     }
 }
@@ -1,5 +1,6 @@
 #[cfg(feature = "variant")]
 #[test]
+#[allow(deprecated)] // uci::Uci
 fn test_zobrist_reference() {
     use serde::Deserialize;
     use serde_with::{